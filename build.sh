@@ -1,7 +1,7 @@
 #!/bin/bash
 cd "$(dirname "$0")"
 
-cargo build --release --target wasm32-unknown-unknown
+cargo build --release --target wasm32-unknown-unknown --bin the-just-two
 
 wasm-bindgen --out-dir ./dist/ --target web ./target/wasm32-unknown-unknown/release/the-just-two.wasm 
 
@@ -7,6 +7,7 @@ pub enum AppState {
     #[default]
     LoadingMenu,
     MainMenu,
+    StorySelect,
     Credits,
     InGame,
 }
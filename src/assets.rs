@@ -1,22 +1,35 @@
 use bevy::prelude::*;
-use bevy_asset_loader::prelude::{AssetCollection, LoadingState, LoadingStateAppExt};
+use bevy_asset_loader::prelude::{
+    AssetCollection, LoadingState, LoadingStateAppExt, StandardDynamicAssetCollection,
+};
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
 
 use crate::{
     app_state::AppState,
     in_game::{
         encounter::encounter_setup_types::Encounters, mission::mission_types::Missions,
-        story::Story, Challengers, Locations, Players,
+        story::Story, world_map::map_types::WorldMapLayout, Challengers, Locations, Players,
     },
 };
 
 pub struct MainGameAssetPlugin;
 
+/// Lists the stories to load. Natively that's everything in the `stories` folder,
+/// but folders can't be read on the web, so there the files are listed out.
+#[cfg(not(target_arch = "wasm32"))]
+const STORIES_ASSET_FILE: &str = "stories.assets.ron";
+#[cfg(target_arch = "wasm32")]
+const STORIES_ASSET_FILE: &str = "stories.web.assets.ron";
+
 impl Plugin for MainGameAssetPlugin {
     fn build(&self, app: &mut App) {
         app.add_loading_state(
             LoadingState::new(AppState::LoadingMenu).continue_to_state(AppState::MainMenu),
         )
+        .add_dynamic_collection_to_loading_state::<_, StandardDynamicAssetCollection>(
+            AppState::LoadingMenu,
+            STORIES_ASSET_FILE,
+        )
         .add_collection_to_loading_state::<_, MainGameAssets>(AppState::LoadingMenu);
     }
 }
@@ -55,8 +68,8 @@ pub struct MainGameAssets {
     pub locations: Handle<Locations>,
     #[asset(path = "players.pl.yaml")]
     pub players: Handle<Players>,
-    #[asset(path = "story.st.yaml")]
-    pub story: Handle<Story>,
+    #[asset(key = "stories", collection(typed))]
+    pub stories: Vec<Handle<Story>>,
     #[asset(path = "missions.ms.yaml")]
     pub missions: Handle<Missions>,
     #[asset(path = "encounters.en.yaml")]
@@ -66,4 +79,6 @@ pub struct MainGameAssets {
     pub default_color_pallet: Handle<Image>,
     #[asset(path = "models/world-map.gltf#Scene0")]
     pub world_map: Handle<Scene>,
+    #[asset(path = "world_map.wm.yaml")]
+    pub world_map_layout: Handle<WorldMapLayout>,
 }
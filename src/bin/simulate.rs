@@ -0,0 +1,172 @@
+use std::path::Path;
+
+use bevy_turborand::{DelegatedRng, RngComponent};
+
+use the_just_two::in_game::{
+    content::LoadedContent,
+    encounter::simulation::{EncounterOutcome, PlayerPolicy, SimulatedEncounter},
+};
+
+const USAGE: &str = "Usage: simulate [--runs N] [--seed N] [--policy random|greedy|focus-weakest] [--encounter KEY] [--assets DIR]";
+
+/// Plays encounters headlessly for balance testing.
+fn main() {
+    let options = match SimulationOptions::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = simulate(&options) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+struct SimulationOptions {
+    runs: usize,
+    seed: u64,
+    policy: PlayerPolicy,
+    encounter: Option<String>,
+    assets: String,
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        Self {
+            runs: 1000,
+            seed: 0,
+            policy: PlayerPolicy::default(),
+            encounter: None,
+            assets: "assets".to_string(),
+        }
+    }
+}
+
+impl SimulationOptions {
+    fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let Some(value) = args.next() else {
+                return Err(format!("Missing value for {arg}"));
+            };
+            let parsed = match arg.as_str() {
+                "--runs" => value.parse().map(|v| options.runs = v).is_ok(),
+                "--seed" => value.parse().map(|v| options.seed = v).is_ok(),
+                "--policy" => PlayerPolicy::parse(&value)
+                    .map(|v| options.policy = v)
+                    .is_some(),
+                "--encounter" => {
+                    options.encounter = Some(value.clone());
+                    true
+                }
+                "--assets" => {
+                    options.assets = value.clone();
+                    true
+                }
+                _ => return Err(format!("Unknown argument {arg}")),
+            };
+            if !parsed {
+                return Err(format!("Invalid value {value} for {arg}"));
+            }
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Default)]
+struct Summary {
+    runs: usize,
+    wins: usize,
+    fled: usize,
+    turns: usize,
+    damage_taken: usize,
+}
+
+impl Summary {
+    fn add(&mut self, outcome: EncounterOutcome) {
+        self.runs += 1;
+        self.wins += usize::from(outcome.won);
+        self.fled += usize::from(outcome.fled);
+        self.turns += outcome.turns;
+        self.damage_taken += outcome.damage_taken;
+    }
+
+    fn average(&self, total: usize) -> f32 {
+        total as f32 / self.runs.max(1) as f32
+    }
+}
+
+fn simulate(options: &SimulationOptions) -> Result<(), String> {
+    let LoadedContent {
+        encounters,
+        challengers,
+        locations,
+        players,
+        ..
+    } = LoadedContent::load(Path::new(&options.assets))?;
+
+    let mut keys = encounters
+        .0
+        .keys()
+        .filter(|key| options.encounter.as_ref().map_or(true, |e| e == *key))
+        .collect::<Vec<_>>();
+    keys.sort();
+    if keys.is_empty() {
+        return Err("No matching encounters".to_string());
+    }
+
+    println!(
+        "Simulating {} runs per encounter with the {:?} policy (seed {})",
+        options.runs, options.policy, options.seed
+    );
+    println!(
+        "{:<32} {:>8} {:>9} {:>10} {:>12}",
+        "encounter", "win rate", "fled", "avg turns", "avg damage"
+    );
+
+    let mut rng = RngComponent::with_seed(options.seed);
+    for key in keys {
+        let mut summary = Summary::default();
+        let mut skipped = 0;
+        for _ in 0..options.runs {
+            // Generated challengers are picked again for every run.
+            let details = encounters.0[key].with_generated_challengers(
+                &challengers,
+                &locations,
+                rng.get_mut(),
+            );
+            let Some(encounter) =
+                SimulatedEncounter::new(&details, &challengers, &players, &locations)
+            else {
+                break;
+            };
+            skipped = skipped.max(encounter.skipped);
+            if !encounter.has_challengers() {
+                continue;
+            }
+            summary.add(encounter.play(options.policy, rng.get_mut()));
+        }
+        if summary.runs == 0 {
+            if skipped > 0 {
+                println!("{key:<32} skipped - only challengers without health");
+            } else {
+                println!("{key:<32} skipped - missing player or location");
+            }
+            continue;
+        }
+        println!(
+            "{:<32} {:>7.1}% {:>8.1}% {:>10.2} {:>12.2}",
+            key,
+            summary.average(summary.wins) * 100.,
+            summary.average(summary.fled) * 100.,
+            summary.average(summary.turns),
+            summary.average(summary.damage_taken),
+        );
+        if skipped > 0 {
+            println!("{:<32} ignored {skipped} challenger(s) without health", "");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,31 @@
+use std::path::Path;
+
+use the_just_two::in_game::content::LoadedContent;
+
+/// Returns the assets directory to check if the first argument is `check`.
+pub fn from_args(mut args: impl Iterator<Item = String>) -> Option<String> {
+    if args.next().as_deref() != Some("check") {
+        return None;
+    }
+    Some(args.next().unwrap_or_else(|| "assets".to_string()))
+}
+
+/// Validates the content files, exiting with a failure if anything is wrong.
+pub fn run(assets: &str) {
+    let content = match LoadedContent::load(Path::new(assets)) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let errors = content.content().validate();
+    for e in errors.iter() {
+        eprintln!("{e}");
+    }
+    if !errors.is_empty() {
+        eprintln!("Found {} content errors", errors.len());
+        std::process::exit(1);
+    }
+    println!("Content is valid");
+}
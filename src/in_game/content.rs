@@ -0,0 +1,494 @@
+use std::{fmt, fs, path::Path};
+
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+
+use crate::{app_state::AppState, assets::MainGameAssets};
+
+use super::{
+    encounter::{
+        actions::{ActionChoice, ActionDefinition, Consequence, Targeting},
+        dice_pools::{DicePoolType, MAX_POOL_SIZE},
+        encounter_setup_types::Encounters,
+    },
+    mission::mission_types::Missions,
+    story::{Story, StoryCondition, StoryTarget},
+    world_map::{map_types::WorldMapLayout, NUM_LOCATIONS_ON_MAP},
+    Challengers, Locations, Players,
+};
+
+pub const STORIES_DIR: &str = "stories";
+pub const MISSIONS_FILE: &str = "missions.ms.yaml";
+pub const ENCOUNTERS_FILE: &str = "encounters.en.yaml";
+pub const CHALLENGERS_FILE: &str = "challengers.ch.yaml";
+pub const LOCATIONS_FILE: &str = "locations.lc.yaml";
+pub const PLAYERS_FILE: &str = "players.pl.yaml";
+pub const WORLD_MAP_FILE: &str = "world_map.wm.yaml";
+
+pub struct ContentValidationPlugin;
+
+impl Plugin for ContentValidationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ContentErrors>()
+            .add_systems(OnExit(AppState::LoadingMenu), validate_loaded_content);
+    }
+}
+
+/// A problem with the authored YAML content, located by file and key.
+#[derive(Clone, Debug)]
+pub struct ContentError {
+    pub file: String,
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for ContentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]: {}", self.file, self.key, self.message)
+    }
+}
+
+/// The errors found when the content finished loading.
+#[derive(Resource, Default)]
+pub struct ContentErrors(pub Vec<ContentError>);
+
+/// All of the content files, read straight from disk for headless tools.
+pub struct LoadedContent {
+    /// Every story in the stories folder, along with its path.
+    pub stories: Vec<(String, Story)>,
+    pub missions: Missions,
+    pub encounters: Encounters,
+    pub challengers: Challengers,
+    pub locations: Locations,
+    pub players: Players,
+    pub world_map: WorldMapLayout,
+}
+
+impl LoadedContent {
+    pub fn load(directory: &Path) -> Result<Self, String> {
+        Ok(Self {
+            stories: load_stories(directory)?,
+            missions: load(&directory.join(MISSIONS_FILE))?,
+            encounters: load(&directory.join(ENCOUNTERS_FILE))?,
+            challengers: load(&directory.join(CHALLENGERS_FILE))?,
+            locations: load(&directory.join(LOCATIONS_FILE))?,
+            players: load(&directory.join(PLAYERS_FILE))?,
+            world_map: load(&directory.join(WORLD_MAP_FILE))?,
+        })
+    }
+
+    pub fn content(&self) -> Content<'_> {
+        Content {
+            stories: self
+                .stories
+                .iter()
+                .map(|(file, story)| (file.clone(), story))
+                .collect(),
+            missions: &self.missions,
+            encounters: &self.encounters,
+            challengers: &self.challengers,
+            locations: &self.locations,
+            players: &self.players,
+            world_map: &self.world_map,
+        }
+    }
+}
+
+fn load_stories(directory: &Path) -> Result<Vec<(String, Story)>, String> {
+    let folder = directory.join(STORIES_DIR);
+    let entries = fs::read_dir(&folder).map_err(|e| format!("{}: {e}", folder.display()))?;
+    let mut files = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.ends_with(".st.yaml"))
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+        .into_iter()
+        .map(|name| Ok((format!("{STORIES_DIR}/{name}"), load(&folder.join(&name))?)))
+        .collect()
+}
+
+fn load<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    serde_yaml::from_str(&data).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+pub struct Content<'a> {
+    pub stories: Vec<(String, &'a Story)>,
+    pub missions: &'a Missions,
+    pub encounters: &'a Encounters,
+    pub challengers: &'a Challengers,
+    pub locations: &'a Locations,
+    pub players: &'a Players,
+    pub world_map: &'a WorldMapLayout,
+}
+
+impl<'a> Content<'a> {
+    /// Checks every reference from the story down to challengers, locations and
+    /// players, along with the thresholds of every action.
+    pub fn validate(&self) -> Vec<ContentError> {
+        let mut errors = vec![];
+        let mut report = |file: &str, key: &str, message: String| {
+            errors.push(ContentError {
+                file: file.to_string(),
+                key: key.to_string(),
+                message,
+            })
+        };
+
+        if self.stories.is_empty() {
+            report(STORIES_DIR, "", "there are no stories".to_string());
+        }
+        for (file, story) in self.stories.iter() {
+            for (index, phase) in story.phases.iter().enumerate() {
+                let key = format!("phases[{index}]");
+                if phase.min_missions > phase.max_missions {
+                    report(
+                        file,
+                        &key,
+                        format!(
+                            "min_missions ({}) is more than max_missions ({})",
+                            phase.min_missions, phase.max_missions
+                        ),
+                    );
+                }
+                for mission in phase.missions.iter() {
+                    if !self.missions.0.contains_key(mission) {
+                        report(file, &key, format!("unknown mission `{mission}`"));
+                    }
+                }
+                for message in phase.rewards.iter().flat_map(|r| r.errors()) {
+                    report(file, &key, message);
+                }
+                for transition in phase.next.iter() {
+                    match &transition.to {
+                        StoryTarget::Phase(to) if !story.phases.iter().any(|p| &p.key == to) => {
+                            report(file, &key, format!("unknown phase `{to}`"))
+                        }
+                        StoryTarget::Ending(to) if !story.endings.iter().any(|e| &e.key == to) => {
+                            report(file, &key, format!("unknown ending `{to}`"))
+                        }
+                        _ => {}
+                    }
+                    for condition in transition.conditions.iter() {
+                        if let StoryCondition::MissionCompleted(mission) = condition {
+                            if !self.missions.0.contains_key(mission) {
+                                report(file, &key, format!("unknown mission `{mission}`"));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        for (key, mission) in sorted(self.missions.0.iter()) {
+            if mission.start.is_empty() {
+                report(MISSIONS_FILE, key, "has no start nodes".to_string());
+            }
+            let known = |node: &str| mission.nodes.iter().any(|n| n.key == node);
+            for node in mission.start.iter().filter(|node| !known(node)) {
+                report(MISSIONS_FILE, key, format!("unknown start node `{node}`"));
+            }
+            for (index, node) in mission.nodes.iter().enumerate() {
+                if mission.nodes[..index].iter().any(|n| n.key == node.key) {
+                    report(
+                        MISSIONS_FILE,
+                        key,
+                        format!("node `{}` is defined twice", node.key),
+                    );
+                }
+                if node.encounters.is_empty() {
+                    report(
+                        MISSIONS_FILE,
+                        key,
+                        format!("node `{}` has no encounters", node.key),
+                    );
+                }
+                for encounter in node.encounters.iter() {
+                    match self.encounters.0.get(encounter) {
+                        None => report(
+                            MISSIONS_FILE,
+                            key,
+                            format!("unknown encounter `{encounter}`"),
+                        ),
+                        Some(details)
+                            if node.budget.is_none()
+                                && details.generated.as_ref().is_some_and(|p| p.budget == 0) =>
+                        {
+                            report(
+                                MISSIONS_FILE,
+                                key,
+                                format!(
+                                    "node `{}` generates challengers for `{encounter}` without a budget",
+                                    node.key
+                                ),
+                            )
+                        }
+                        _ => {}
+                    }
+                }
+                for edge in node.edges.iter().filter(|edge| !known(&edge.to)) {
+                    report(
+                        MISSIONS_FILE,
+                        key,
+                        format!("node `{}` leads to unknown node `{}`", node.key, edge.to),
+                    );
+                }
+            }
+            for message in mission.rewards.iter().flat_map(|r| r.errors()) {
+                report(MISSIONS_FILE, key, message);
+            }
+            for location in mission.locations.iter() {
+                if self.world_map.find(location).is_none() {
+                    report(
+                        MISSIONS_FILE,
+                        key,
+                        format!("unknown map location `{location}`"),
+                    );
+                }
+            }
+        }
+
+        if self.world_map.get(self.world_map.start).is_none() {
+            report(
+                WORLD_MAP_FILE,
+                "start",
+                format!("unknown location {}", self.world_map.start),
+            );
+        }
+        for index in self.world_map.indices() {
+            let key = index.to_string();
+            let location = &self.world_map.locations[&index];
+            if index >= NUM_LOCATIONS_ON_MAP {
+                report(
+                    WORLD_MAP_FILE,
+                    &key,
+                    format!("the map only has {NUM_LOCATIONS_ON_MAP} locations"),
+                );
+            }
+            if self.world_map.find(&location.name) != Some(index) {
+                report(
+                    WORLD_MAP_FILE,
+                    &key,
+                    format!("`{}` is used by more than one location", location.name),
+                );
+            }
+            for link in location.links.iter() {
+                if self.world_map.get(*link).is_none() {
+                    report(
+                        WORLD_MAP_FILE,
+                        &key,
+                        format!("links to unknown location {link}"),
+                    );
+                }
+            }
+        }
+
+        for (key, encounter) in sorted(self.encounters.0.iter()) {
+            let player = encounter.player_faction.player_key();
+            if self.players.get(player).is_none() {
+                report(ENCOUNTERS_FILE, key, format!("unknown player `{player}`"));
+            }
+            let location = encounter
+                .location
+                .as_ref()
+                .and_then(|location| self.locations.get(location));
+            match &encounter.location {
+                Some(location) if self.locations.get(location).is_none() => report(
+                    ENCOUNTERS_FILE,
+                    key,
+                    format!("unknown location `{location}`"),
+                ),
+                None => report(ENCOUNTERS_FILE, key, "has no location".to_string()),
+                _ => {}
+            }
+            if let (Some(pool), Some(location)) = (&encounter.generated, location) {
+                if pool.candidates(self.challengers, location).is_empty() {
+                    report(
+                        ENCOUNTERS_FILE,
+                        key,
+                        format!(
+                            "no challengers with a threat fit the tags {:?} at `{}`",
+                            pool.tags, location.name
+                        ),
+                    );
+                }
+            }
+            for (_, challenger) in encounter.challengers.iter() {
+                if self.challengers.get(challenger).is_none() {
+                    report(
+                        ENCOUNTERS_FILE,
+                        key,
+                        format!("unknown challenger `{challenger}`"),
+                    );
+                }
+            }
+        }
+
+        for (key, challenger) in sorted(self.challengers.iter()) {
+            for action in challenger
+                .available_actions
+                .iter()
+                .chain(challenger.published_actions.iter())
+            {
+                for message in action_errors(action) {
+                    report(CHALLENGERS_FILE, key, message);
+                }
+            }
+        }
+
+        for (key, player) in sorted(self.players.iter()) {
+            for action in player.combat_actions.iter() {
+                for message in action_errors(action) {
+                    report(PLAYERS_FILE, key, message);
+                }
+            }
+            let mut previous_xp = 0;
+            for (index, level) in player.levels.iter().enumerate() {
+                if level.xp < previous_xp {
+                    report(
+                        PLAYERS_FILE,
+                        key,
+                        format!(
+                            "levels[{index}] needs less XP ({}) than the level before it ({previous_xp})",
+                            level.xp
+                        ),
+                    );
+                }
+                previous_xp = level.xp;
+                for action in level.combat_actions.iter() {
+                    for message in action_errors(action) {
+                        report(PLAYERS_FILE, key, message);
+                    }
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+fn sorted<'a, T>(items: impl Iterator<Item = (&'a String, &'a T)>) -> Vec<(&'a String, &'a T)> {
+    let mut items = items.collect::<Vec<_>>();
+    items.sort_by_key(|(key, _)| *key);
+    items
+}
+
+/// Checks an action along with any follow-up actions its text outcomes offer.
+fn action_errors(action: &ActionDefinition) -> Vec<String> {
+    let outcomes = &action.outcomes;
+    let targeting_error = match action.targeting {
+        Targeting::Cleave { count: 0, .. } => Some(format!(
+            "`{}` cleaves without hitting anyone - count is 0",
+            action.choice.title
+        )),
+        _ => None,
+    };
+    threshold_error(&action.choice)
+        .into_iter()
+        .chain(pool_errors(&action.choice))
+        .chain(targeting_error)
+        .chain(
+            [
+                &outcomes.critical_fail,
+                &outcomes.fail,
+                &outcomes.success,
+                &outcomes.critical_success,
+            ]
+            .into_iter()
+            .flatten()
+            .flat_map(|outcome| outcome.consequences.iter())
+            .flat_map(|consequence| match consequence {
+                Consequence::FollowUp(follow_up) => action_errors(follow_up),
+                _ => vec![],
+            }),
+        )
+        .collect()
+}
+
+fn pool_errors(choice: &ActionChoice) -> Vec<String> {
+    choice
+        .dice_pool
+        .iter()
+        .filter_map(|pool| match pool.pool {
+            DicePoolType::KeepHighest { rolled, .. } | DicePoolType::KeepLowest { rolled, .. }
+                if rolled > MAX_POOL_SIZE =>
+            {
+                Some(format!(
+                    "`{}` rolls {rolled} dice in one pool - the most is {MAX_POOL_SIZE}",
+                    choice.title
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn threshold_error(choice: &ActionChoice) -> Option<String> {
+    if choice.fail <= choice.success && choice.success <= choice.critical_success {
+        return None;
+    }
+    Some(format!(
+        "`{}` thresholds are out of order - fail {}, success {}, critical_success {}",
+        choice.title, choice.fail, choice.success, choice.critical_success
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn validate_loaded_content(
+    mut content_errors: ResMut<ContentErrors>,
+    assets: Res<MainGameAssets>,
+    asset_server: Res<AssetServer>,
+    stories: Res<Assets<Story>>,
+    missions: Res<Assets<Missions>>,
+    encounters: Res<Assets<Encounters>>,
+    challengers: Res<Assets<Challengers>>,
+    locations: Res<Assets<Locations>>,
+    players: Res<Assets<Players>>,
+    world_maps: Res<Assets<WorldMapLayout>>,
+) {
+    let (
+        Some(missions),
+        Some(encounters),
+        Some(challengers),
+        Some(locations),
+        Some(players),
+        Some(world_map),
+    ) = (
+        missions.get(&assets.missions),
+        encounters.get(&assets.encounters),
+        challengers.get(&assets.challengers),
+        locations.get(&assets.locations),
+        players.get(&assets.players),
+        world_maps.get(&assets.world_map_layout),
+    )
+    else {
+        return;
+    };
+    let stories = assets
+        .stories
+        .iter()
+        .filter_map(|handle| {
+            let file = asset_server
+                .get_handle_path(handle)
+                .map(|path| path.path().display().to_string())
+                .unwrap_or_default();
+            stories.get(handle).map(|story| (file, story))
+        })
+        .collect();
+    let errors = Content {
+        stories,
+        missions,
+        encounters,
+        challengers,
+        locations,
+        players,
+        world_map,
+    }
+    .validate();
+    for e in errors.iter() {
+        error!("{e}");
+    }
+    content_errors.0 = errors;
+}
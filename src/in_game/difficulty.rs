@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use serde::{Deserialize, Serialize};
+
+use super::encounter::{
+    actions::{ActionChoice, ActionType},
+    dice_distribution::Distribution,
+};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Difficulty>()
+            .register_type::<DifficultyMultipliers>()
+            .init_resource::<Difficulty>();
+    }
+}
+
+/// The multipliers a custom difficulty steps through.
+const MULTIPLIER_STEPS: [f32; 7] = [0.5, 0.75, 1., 1.25, 1.5, 1.75, 2.];
+
+/// How hard challengers are. Picked on the main menu and kept with the run.
+#[derive(
+    Resource,
+    Reflect,
+    InspectorOptions,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Serialize,
+    Deserialize,
+)]
+#[reflect(Resource, InspectorOptions)]
+pub enum Difficulty {
+    Story,
+    #[default]
+    Normal,
+    Hard,
+    Custom(DifficultyMultipliers),
+}
+
+#[derive(Reflect, InspectorOptions, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[reflect(InspectorOptions)]
+pub struct DifficultyMultipliers {
+    pub health: f32,
+    pub damage: f32,
+    /// Scales the thresholds of challenger actions - higher makes them harder to defend against.
+    pub thresholds: f32,
+}
+
+impl Default for DifficultyMultipliers {
+    fn default() -> Self {
+        Self {
+            health: 1.,
+            damage: 1.,
+            thresholds: 1.,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DifficultySetting {
+    Health,
+    Damage,
+    Thresholds,
+}
+
+impl Difficulty {
+    pub fn multipliers(&self) -> DifficultyMultipliers {
+        match self {
+            Difficulty::Story => DifficultyMultipliers {
+                health: 0.6,
+                damage: 0.5,
+                thresholds: 0.8,
+            },
+            Difficulty::Normal => DifficultyMultipliers::default(),
+            Difficulty::Hard => DifficultyMultipliers {
+                health: 1.5,
+                damage: 1.5,
+                thresholds: 1.2,
+            },
+            Difficulty::Custom(multipliers) => *multipliers,
+        }
+    }
+
+    /// The next preset, for cycling through them on the menu.
+    pub fn next(&self) -> Self {
+        match self {
+            Difficulty::Story => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Custom(self.multipliers()),
+            Difficulty::Custom(_) => Difficulty::Story,
+        }
+    }
+
+    /// Steps one multiplier up, wrapping back to the lowest, which makes the difficulty custom.
+    pub fn step(&self, setting: DifficultySetting) -> Self {
+        let mut multipliers = self.multipliers();
+        let value = match setting {
+            DifficultySetting::Health => &mut multipliers.health,
+            DifficultySetting::Damage => &mut multipliers.damage,
+            DifficultySetting::Thresholds => &mut multipliers.thresholds,
+        };
+        *value = MULTIPLIER_STEPS
+            .iter()
+            .copied()
+            .find(|step| *step > *value + f32::EPSILON)
+            .unwrap_or(MULTIPLIER_STEPS[0]);
+        Difficulty::Custom(multipliers)
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            Difficulty::Custom(_) => "Difficulty: Custom".to_string(),
+            preset => format!("Difficulty: {preset:?}"),
+        }
+    }
+
+    pub fn setting_label(&self, setting: DifficultySetting) -> String {
+        let multipliers = self.multipliers();
+        match setting {
+            DifficultySetting::Health => format!("Health x{:.2}", multipliers.health),
+            DifficultySetting::Damage => format!("Damage x{:.2}", multipliers.damage),
+            DifficultySetting::Thresholds => format!("Thresholds x{:.2}", multipliers.thresholds),
+        }
+    }
+
+    pub fn health(&self, health: usize) -> usize {
+        scale(health, self.multipliers().health).max(1)
+    }
+
+    pub fn action_type(&self, action_type: &ActionType) -> ActionType {
+        match action_type {
+            ActionType::Attack { base_damage } if *base_damage > 0 => ActionType::Attack {
+                base_damage: scale_u8(*base_damage, self.multipliers().damage).max(1),
+            },
+            action_type => action_type.clone(),
+        }
+    }
+
+    /// Scales an action's thresholds, keeping them in order. Scaling never pushes a
+    /// critical success past the highest roll of the action's dice, unless it already was.
+    pub fn choice(&self, choice: ActionChoice) -> ActionChoice {
+        let thresholds = self.multipliers().thresholds;
+        if (thresholds - 1.).abs() < f32::EPSILON {
+            return choice;
+        }
+        let pools = choice.dice_pool.iter().collect::<Vec<_>>();
+        let highest = pools.as_slice().distribution().highest();
+        let critical_success = scale_u8(choice.critical_success, thresholds)
+            .min(highest.max(choice.critical_success))
+            .max(2);
+        let success = scale_u8(choice.success, thresholds).min(critical_success);
+        let fail = scale_u8(choice.fail, thresholds).min(success);
+        ActionChoice {
+            fail,
+            success,
+            critical_success,
+            ..choice
+        }
+    }
+}
+
+fn scale(value: usize, multiplier: f32) -> usize {
+    (value as f32 * multiplier).round().max(0.) as usize
+}
+
+fn scale_u8(value: u8, multiplier: f32) -> u8 {
+    scale(value as usize, multiplier).min(u8::MAX as usize) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::in_game::encounter::dice_pools::{DicePool, InitialPools};
+
+    use super::*;
+
+    fn two_d6(fail: u8, success: u8, critical_success: u8) -> ActionChoice {
+        ActionChoice {
+            fail,
+            success,
+            critical_success,
+            dice_pool: InitialPools::new(vec![DicePool::d6(), DicePool::d6()]),
+            ..Default::default()
+        }
+    }
+
+    fn thresholds(choice: &ActionChoice) -> (u8, u8, u8) {
+        (choice.fail, choice.success, choice.critical_success)
+    }
+
+    #[test]
+    fn normal_leaves_thresholds_alone() {
+        let choice = Difficulty::Normal.choice(two_d6(3, 9, 9));
+        assert_eq!(thresholds(&choice), (3, 9, 9));
+    }
+
+    #[test]
+    fn story_lowers_thresholds() {
+        let choice = Difficulty::Story.choice(two_d6(2, 6, 9));
+        assert_eq!(thresholds(&choice), (2, 5, 7));
+
+        let choice = Difficulty::Story.choice(two_d6(3, 9, 9));
+        assert_eq!(thresholds(&choice), (2, 7, 7));
+    }
+
+    #[test]
+    fn hard_raises_thresholds_up_to_the_highest_roll() {
+        let choice = Difficulty::Hard.choice(two_d6(2, 6, 9));
+        assert_eq!(thresholds(&choice), (2, 7, 11));
+
+        let choice = Difficulty::Hard.choice(two_d6(4, 10, 11));
+        assert_eq!(thresholds(&choice), (5, 12, 12));
+    }
+}
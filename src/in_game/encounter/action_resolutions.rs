@@ -1,9 +1,13 @@
-use bevy::prelude::*;
+use bevy::{ecs::query::Has, prelude::*};
 
 use crate::in_game::InGameUpdate;
 
 use super::{
-    actions::{ActionChoice, ChallengerAction, Resolution},
+    actions::{ActionChoice, ActionType, Fled, Resolution},
+    challenger::Challenger,
+    encounter_resolution::ChallengerCompleted,
+    health::CurrentHealth,
+    initiative::{turn_order, Initiative},
     sequencing::EncounterState,
 };
 
@@ -11,9 +15,13 @@ pub struct ActionResolutionPlugin;
 
 impl Plugin for ActionResolutionPlugin {
     fn build(&self, app: &mut App) {
+        // Once the player has fled, the flee action ends the encounter instead.
         app.add_systems(
             InGameUpdate,
-            trigger_next_resolution.run_if(in_state(EncounterState::OutcomeResolution)),
+            trigger_next_resolution.run_if(
+                in_state(EncounterState::OutcomeResolution)
+                    .and_then(not(resource_exists::<Fled>())),
+            ),
         );
     }
 }
@@ -21,34 +29,59 @@ impl Plugin for ActionResolutionPlugin {
 #[derive(Component)]
 pub struct ActiveResolution;
 
-type ResolvedActionsQuery<'w, 's, 'a> =
-    Query<'w, 's, (Entity, Option<&'a ChallengerAction>), (With<ActionChoice>, With<Resolution>)>;
+type ResolvedActionsQuery<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (Entity, &'a ActionType, Option<&'a Parent>),
+    (With<ActionChoice>, With<Resolution>),
+>;
+
+type ActorsQuery<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        &'a Initiative,
+        Option<&'a Challenger>,
+        Option<&'a CurrentHealth>,
+        Has<ChallengerCompleted>,
+    ),
+>;
 
 fn trigger_next_resolution(
     mut commands: Commands,
     resolved_actions: ResolvedActionsQuery,
+    actors: ActorsQuery,
     active_resolution: Query<Entity, With<ActiveResolution>>,
 ) {
     if !active_resolution.is_empty() {
         return;
     }
     info!("Looking for next resolution!");
-    let next_action = resolved_actions.iter().fold(
-        None,
-        |resolved: Option<(Entity, Option<&ChallengerAction>)>, (entity, challanger_action)| {
-            if let Some(resolved) = resolved {
-                match resolved.1 {
-                    Some(_) => Some((entity, challanger_action)),
-                    None => Some(resolved),
-                }
-            } else {
-                Some((entity, challanger_action))
-            }
-        },
-    );
+    let mut next_action = None;
+    for (entity, action_type, actor) in resolved_actions.iter() {
+        let Some(Ok((initiative, challenger, health, completed))) =
+            actor.map(|p| actors.get(p.get()))
+        else {
+            continue;
+        };
+        // Anyone taken down earlier in the round loses their turn.
+        if completed || health.map(|h| h.0 == 0).unwrap_or(false) {
+            info!("Skipping {entity:?}, its actor is down");
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        // Defending goes first, then everything else in initiative order.
+        let key = (
+            action_type.resolution_priority(),
+            turn_order(*initiative, challenger.map(|c| c.id)),
+        );
+        if next_action.as_ref().map_or(true, |(next, _)| key < *next) {
+            next_action = Some((key, entity));
+        }
+    }
 
     match next_action {
-        Some((entity, _)) => {
+        Some((_, entity)) => {
             commands.entity(entity).insert(ActiveResolution);
         }
         None => {
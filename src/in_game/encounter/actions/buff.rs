@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::MainGameAssets,
+    in_game::encounter::{
+        encounter_log::EncounterLog, encounter_setup_types::EncounterSetup,
+        sequencing::EncounterState, EncounterEntity,
+    },
+};
+
+use super::{
+    resolution_screen::{display_resolution, icons, line, CurrentResolutionQuery},
+    ActionType,
+};
+
+pub struct BuffActionPlugin;
+
+impl Plugin for BuffActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            display_buff_resolution.run_if(in_state(EncounterState::OutcomeResolution)),
+        )
+        .add_systems(
+            OnExit(EncounterState::ProbabilitySetup),
+            expire_temporary_powers,
+        );
+    }
+}
+
+/// A power granted by a buff, which is lost after the next probability setup.
+#[derive(Component)]
+pub struct TemporaryPower;
+
+fn display_buff_resolution(
+    mut commands: Commands,
+    resolution: CurrentResolutionQuery,
+    setup: Res<EncounterSetup>,
+    mut log: ResMut<EncounterLog>,
+    asset_server: Res<AssetServer>,
+    assets: Res<MainGameAssets>,
+) {
+    let Ok((choice, resolution, action_type, _, is_challenger, ())) = resolution.get_single()
+    else {
+        return;
+    };
+    let ActionType::Buff { power } = action_type else {
+        return;
+    };
+    log.record(&choice.title, resolution, None);
+    // Powers belong to the player, so a challenger's buff has nothing to grant.
+    let granted = if !is_challenger && resolution.result.lands(is_challenger) {
+        resolution.result.scale(1)
+    } else {
+        0
+    };
+    for _ in 0..granted {
+        commands.spawn((
+            *power,
+            setup.player_faction,
+            TemporaryPower,
+            EncounterEntity,
+        ));
+    }
+
+    display_resolution(
+        &mut commands,
+        &asset_server,
+        &assets,
+        choice,
+        resolution,
+        |p| {
+            if granted > 0 {
+                vec![
+                    line("Gained for the next roll:", p),
+                    icons(std::iter::repeat(power).take(granted as usize), &assets, p),
+                ]
+            } else {
+                vec![line("Nothing was gained", p)]
+            }
+        },
+    );
+}
+
+fn expire_temporary_powers(mut commands: Commands, powers: Query<Entity, With<TemporaryPower>>) {
+    for entity in powers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
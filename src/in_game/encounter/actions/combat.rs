@@ -1,24 +1,21 @@
-use bevy::{ecs::query::Has, prelude::*};
-use bevy_ui_dsl::*;
+use bevy::prelude::*;
 
 use crate::{
+    assets::MainGameAssets,
     in_game::{
         encounter::{
-            action_resolutions::ActiveResolution, challenger::Challenger,
+            challenger::Challenger, encounter_log::EncounterLog,
             encounter_resolution::ChallengerCompleted, health::CurrentHealth, player::Player,
-            sequencing::EncounterState,
+            sequencing::EncounterState, status_effects::StatusEffects,
         },
         game_state::GameState,
-        InGameUpdate,
-    },
-    ui::{
-        buttons::{focus_text_button, focused_button_activated},
-        classes::*,
-        intermediary_node_bundles::IntoIntermediaryNodeBundle,
     },
 };
 
-use super::{ActionChoice, ActionTarget, ActionType, ChallengerAction, Resolution};
+use super::{
+    resolution_screen::{display_resolution, line, CurrentResolutionQuery},
+    ActionTarget, ActionType, Defending, Targeting,
+};
 
 pub struct CombatActionPlugin;
 
@@ -31,111 +28,91 @@ impl Plugin for CombatActionPlugin {
         .add_systems(
             OnExit(EncounterState::OutcomeResolution),
             end_combat_encounter,
-        )
-        .add_systems(
-            InGameUpdate,
-            (focused_button_activated.pipe(process_input))
-                .run_if(in_state(EncounterState::OutcomeResolution)),
         );
     }
 }
-#[derive(Component)]
-struct Screen;
-
-#[derive(Component)]
-pub struct Button;
-
-type CurrentResolutionQuery<'w, 's, 'a> = Query<
-    'w,
-    's,
-    (
-        Entity,
-        &'a ActionChoice,
-        &'a Resolution,
-        &'a ActionType,
-        &'a ActionTarget,
-        Has<ChallengerAction>,
-    ),
-    Added<ActiveResolution>,
->;
 
 fn display_combat_resolution(
     mut commands: Commands,
-    resolution: CurrentResolutionQuery,
-    mut targetable: Query<&mut CurrentHealth>,
+    resolution: CurrentResolutionQuery<'_, '_, '_, (&ActionTarget, &Targeting)>,
+    mut targetable: Query<(
+        &mut CurrentHealth,
+        Option<&mut StatusEffects>,
+        Option<&Defending>,
+        Option<&Challenger>,
+    )>,
+    mut log: ResMut<EncounterLog>,
     asset_server: Res<AssetServer>,
+    assets: Res<MainGameAssets>,
 ) {
-    let Ok((_entity, choice, resolution, action_type, target, is_challanger)) =
+    let Ok((choice, resolution, action_type, _, is_challanger, (target, targeting))) =
         resolution.get_single()
     else {
         return;
     };
-    let ActionType::Attack { base_damage } = action_type else {
+    if !matches!(action_type, ActionType::Attack { .. }) {
         return;
+    }
+    let result_text = match resolution.result {
+        super::ActionResult::CriticalFail => "Failed Badly",
+        super::ActionResult::Fail => "Failed",
+        super::ActionResult::Success => "Succeeded!",
+        super::ActionResult::CriticalSuccess => "Amazing Success!",
     };
-    let (result_text, damage) = if is_challanger {
-        match resolution.result {
-            super::ActionResult::CriticalFail => ("Failed Badly", (*base_damage) * 2),
-            super::ActionResult::Fail => ("Failed", *base_damage),
-            super::ActionResult::Success => ("Succeeded!", 0),
-            super::ActionResult::CriticalSuccess => ("Amazing Success!", 0),
+    let total_damage = action_type.damage(resolution.result, is_challanger) as usize;
+    let hit = target.0.len();
+    let mut absorbed = 0;
+    let mut dealt = vec![];
+    for (index, target) in target.0.iter().enumerate() {
+        let Ok((mut health, status, defending, challenger)) = targetable.get_mut(*target) else {
+            continue;
+        };
+        let mut damage = targeting.share(total_damage, index, hit);
+        if let Some(Defending(reduction)) = defending {
+            let blocked = damage.min(*reduction as usize);
+            absorbed += blocked;
+            damage -= blocked;
         }
-    } else {
-        match resolution.result {
-            super::ActionResult::CriticalFail => ("Failed Badly", 0),
-            super::ActionResult::Fail => ("Failed", 0),
-            super::ActionResult::Success => ("Succeeded!", *base_damage),
-            super::ActionResult::CriticalSuccess => ("Amazing Success!", (*base_damage) * 2),
+        if let Some(mut status) = status {
+            let remaining = status.absorb(damage);
+            absorbed += damage - remaining;
+            damage = remaining;
         }
-    };
-    if let ActionTarget(Some(target)) = target {
-        if let Ok(mut target) = targetable.get_mut(*target) {
-            info!("Target took damage!");
-            target.0 = target.0.saturating_sub(damage as usize);
+        info!("Target took damage!");
+        health.0 = health.0.saturating_sub(damage);
+        if damage > 0 {
+            dealt.push((challenger.map(|c| c.name.clone()), damage));
         }
     }
-    let mut next_button = None;
-    let root = root(c_root, &asset_server, &mut commands, |p| {
-        node(primary_box, p, |p| {
-            node((span.nb(), primary_box_main.nb()), p, |p| {
-                text(&choice.title, (), (main_text, knight_text), p);
-            });
-            text(result_text, primary_box_item.nb(), standard_text, p);
-
-            if damage > 0 {
-                text(
-                    if is_challanger {
-                        format!("Player took {damage}")
-                    } else {
-                        format!("Target took {damage}")
+    log.record(
+        &choice.title,
+        resolution,
+        Some(dealt.iter().map(|(_, damage)| damage).sum()),
+    );
+    display_resolution(
+        &mut commands,
+        &asset_server,
+        &assets,
+        choice,
+        resolution,
+        |p| {
+            let mut lines = vec![line(result_text, p)];
+            if absorbed > 0 {
+                lines.push(line(format!("Blocked {absorbed}"), p));
+            }
+            for (name, damage) in dealt.iter() {
+                lines.push(line(
+                    match name {
+                        _ if is_challanger => format!("Player took {damage}"),
+                        Some(name) if hit > 1 => format!("{name} took {damage}"),
+                        _ => format!("Target took {damage}"),
                     },
-                    primary_box_item.nb(),
-                    standard_text,
                     p,
-                );
+                ));
             }
-
-            text(
-                format!("Rolled a {}", resolution.roll),
-                primary_box_item.nb(),
-                standard_text,
-                p,
-            );
-            focus_text_button(
-                "Next",
-                (c_button.nb(), primary_box_item.nb()),
-                apply_button_state,
-                button_text,
-                p,
-            )
-            .set(&mut next_button);
-        });
-    });
-
-    commands.entity(root).insert(Screen);
-    if let Some(next_button) = next_button {
-        commands.entity(next_button).insert(Button);
-    }
+            lines
+        },
+    );
 }
 
 type CombatantWithUpdatedHealth<'w, 's, 'a> =
@@ -158,21 +135,3 @@ fn end_combat_encounter(
         }
     }
 }
-
-fn process_input(
-    In(focused): In<Option<Entity>>,
-    mut commands: Commands,
-    screen: Query<Entity, With<Screen>>,
-    resolved_action: Query<Entity, With<ActiveResolution>>,
-) {
-    let Some(_) = focused else {
-        return;
-    };
-    for item in screen.iter() {
-        commands.entity(item).despawn_recursive();
-    }
-
-    for item in resolved_action.iter() {
-        commands.entity(item).despawn_recursive();
-    }
-}
@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::MainGameAssets,
+    in_game::encounter::{encounter_log::EncounterLog, sequencing::EncounterState},
+};
+
+use super::{
+    resolution_screen::{display_resolution, line, CurrentResolutionQuery},
+    ActionType,
+};
+
+pub struct DefendActionPlugin;
+
+impl Plugin for DefendActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            display_defend_resolution.run_if(in_state(EncounterState::OutcomeResolution)),
+        )
+        .add_systems(
+            OnEnter(EncounterState::CheckEncounterResolution),
+            clear_defending,
+        );
+    }
+}
+
+/// Reduces the damage of each attack against this combatant until the end of the round.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Defending(pub u8);
+
+fn display_defend_resolution(
+    mut commands: Commands,
+    resolution: CurrentResolutionQuery,
+    mut log: ResMut<EncounterLog>,
+    asset_server: Res<AssetServer>,
+    assets: Res<MainGameAssets>,
+) {
+    let Ok((choice, resolution, action_type, actor, is_challenger, ())) = resolution.get_single()
+    else {
+        return;
+    };
+    let ActionType::Defend { reduction } = action_type else {
+        return;
+    };
+    log.record(&choice.title, resolution, None);
+    let mut defending = 0;
+    if resolution.result.lands(is_challenger) {
+        if let Some(actor) = actor {
+            defending = resolution.result.scale(*reduction);
+            commands.entity(actor.get()).insert(Defending(defending));
+        }
+    }
+
+    display_resolution(
+        &mut commands,
+        &asset_server,
+        &assets,
+        choice,
+        resolution,
+        |p| {
+            vec![line(
+                if defending == 0 {
+                    "Failed to take a defensive stance".to_string()
+                } else if is_challenger {
+                    format!("The challenger will block {defending} from each attack this round")
+                } else {
+                    format!("Will block {defending} from each attack this round")
+                },
+                p,
+            )]
+        },
+    );
+}
+
+fn clear_defending(mut commands: Commands, defending: Query<Entity, With<Defending>>) {
+    for entity in defending.iter() {
+        commands.entity(entity).remove::<Defending>();
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::MainGameAssets,
+    in_game::{
+        encounter::{
+            action_resolutions::ActiveResolution, encounter_log::EncounterLog,
+            encounter_resolution::ChallengerCompleted, sequencing::EncounterState,
+        },
+        InGameUpdate,
+    },
+};
+
+use super::{
+    resolution_screen::{display_resolution, line, CurrentResolutionQuery},
+    ActionType, Resolution,
+};
+
+pub struct FleeActionPlugin;
+
+impl Plugin for FleeActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            display_flee_resolution.run_if(in_state(EncounterState::OutcomeResolution)),
+        )
+        .add_systems(
+            InGameUpdate,
+            escape_encounter.run_if(
+                in_state(EncounterState::OutcomeResolution).and_then(resource_exists::<Fled>()),
+            ),
+        );
+    }
+}
+
+/// Inserted when the player escapes an encounter, abandoning the rest of the mission.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct Fled;
+
+/// Marks a challenger that got away rather than being defeated.
+#[derive(Component)]
+pub struct Escaped;
+
+fn display_flee_resolution(
+    mut commands: Commands,
+    resolution: CurrentResolutionQuery,
+    mut log: ResMut<EncounterLog>,
+    asset_server: Res<AssetServer>,
+    assets: Res<MainGameAssets>,
+) {
+    let Ok((choice, resolution, action_type, actor, is_challenger, ())) = resolution.get_single()
+    else {
+        return;
+    };
+    if !matches!(action_type, ActionType::Flee) {
+        return;
+    }
+    log.record(&choice.title, resolution, None);
+    let escaped = resolution.result.lands(is_challenger);
+    if escaped {
+        if !is_challenger {
+            commands.insert_resource(Fled);
+        } else if let Some(actor) = actor {
+            commands
+                .entity(actor.get())
+                .insert((ChallengerCompleted, Escaped));
+        }
+    }
+
+    display_resolution(
+        &mut commands,
+        &asset_server,
+        &assets,
+        choice,
+        resolution,
+        |p| {
+            vec![line(
+                match (escaped, is_challenger) {
+                    (true, false) => "Escaped! The rest of the mission is abandoned",
+                    (false, false) => "Couldn't get away",
+                    (true, true) => "The challenger got away",
+                    (false, true) => "The challenger couldn't get away",
+                },
+                p,
+            )]
+        },
+    );
+}
+
+/// Nothing else happens once the player is away, so the encounter ends as soon as
+/// their escape has been shown.
+fn escape_encounter(
+    mut commands: Commands,
+    active_resolution: Query<Entity, With<ActiveResolution>>,
+    pending_actions: Query<Entity, With<Resolution>>,
+) {
+    if !active_resolution.is_empty() {
+        return;
+    }
+    for item in pending_actions.iter() {
+        commands.entity(item).despawn_recursive();
+    }
+    commands.insert_resource(NextState(Some(EncounterState::EncounterResolved)));
+}
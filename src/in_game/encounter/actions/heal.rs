@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::MainGameAssets,
+    in_game::encounter::{
+        encounter_log::EncounterLog,
+        health::{CurrentHealth, MaxHealth},
+        sequencing::EncounterState,
+    },
+};
+
+use super::{
+    resolution_screen::{display_resolution, line, CurrentResolutionQuery},
+    ActionType,
+};
+
+pub struct HealActionPlugin;
+
+impl Plugin for HealActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            display_heal_resolution.run_if(in_state(EncounterState::OutcomeResolution)),
+        );
+    }
+}
+
+fn display_heal_resolution(
+    mut commands: Commands,
+    resolution: CurrentResolutionQuery,
+    mut healable: Query<(&mut CurrentHealth, &MaxHealth)>,
+    mut log: ResMut<EncounterLog>,
+    asset_server: Res<AssetServer>,
+    assets: Res<MainGameAssets>,
+) {
+    let Ok((choice, resolution, action_type, actor, is_challenger, ())) = resolution.get_single()
+    else {
+        return;
+    };
+    let ActionType::Heal { amount } = action_type else {
+        return;
+    };
+    log.record(&choice.title, resolution, None);
+    let mut healed = 0;
+    if resolution.result.lands(is_challenger) {
+        if let Some(Ok((mut current, max))) = actor.map(|p| healable.get_mut(p.get())) {
+            let restored = (current.0 + resolution.result.scale(*amount) as usize).min(max.0);
+            healed = restored.saturating_sub(current.0);
+            current.0 = restored;
+        }
+    }
+
+    display_resolution(
+        &mut commands,
+        &asset_server,
+        &assets,
+        choice,
+        resolution,
+        |p| {
+            vec![line(
+                if healed == 0 {
+                    "No health was restored".to_string()
+                } else if is_challenger {
+                    format!("The challenger recovered {healed}")
+                } else {
+                    format!("Recovered {healed}")
+                },
+                p,
+            )]
+        },
+    );
+}
@@ -1,13 +1,35 @@
+mod buff;
 mod combat;
+mod defend;
+mod flee;
+mod heal;
+mod resolution_screen;
+mod roll_display;
 mod text;
 
 use bevy::{prelude::*, reflect::Reflect};
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use self::{combat::CombatActionPlugin, text::TextActionPlugin};
+use self::{
+    buff::BuffActionPlugin, combat::CombatActionPlugin, defend::DefendActionPlugin,
+    flee::FleeActionPlugin, heal::HealActionPlugin, resolution_screen::ResolutionScreenPlugin,
+    roll_display::RollDisplayPlugin, text::TextActionPlugin,
+};
 
-use super::dice_pools::InitialPools;
+pub use self::{
+    buff::TemporaryPower,
+    defend::Defending,
+    flee::{Escaped, Fled},
+    roll_display::RevealAfterRoll,
+    text::{Consequence, TextOutcome, TextOutcomes},
+};
+
+use super::{
+    dice_pools::{InitialPools, RolledPool},
+    powers::Power,
+    status_effects::{AppliesEffects, StatusEffect},
+};
 
 pub struct ActionPlugin;
 
@@ -17,8 +39,16 @@ impl Plugin for ActionPlugin {
             .register_type::<ActionResult>()
             .register_type::<Resolution>()
             .register_type::<ActionType>()
+            .register_type::<ActionTarget>()
+            .register_type::<Targeting>()
             .add_plugins(TextActionPlugin)
-            .add_plugins(CombatActionPlugin);
+            .add_plugins(CombatActionPlugin)
+            .add_plugins(HealActionPlugin)
+            .add_plugins(DefendActionPlugin)
+            .add_plugins(FleeActionPlugin)
+            .add_plugins(BuffActionPlugin)
+            .add_plugins(ResolutionScreenPlugin)
+            .add_plugins(RollDisplayPlugin);
     }
 }
 
@@ -46,7 +76,7 @@ impl Default for ActionChoice {
     }
 }
 
-#[derive(InspectorOptions, Reflect)]
+#[derive(InspectorOptions, Reflect, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[reflect(InspectorOptions)]
 pub enum ActionResult {
     CriticalFail,
@@ -55,6 +85,24 @@ pub enum ActionResult {
     CriticalSuccess,
 }
 
+impl ActionResult {
+    /// Whether the action takes effect. Challenger actions land when the player fails to defend.
+    pub fn lands(&self, is_challenger: bool) -> bool {
+        match self {
+            ActionResult::CriticalFail | ActionResult::Fail => is_challenger,
+            ActionResult::Success | ActionResult::CriticalSuccess => !is_challenger,
+        }
+    }
+
+    /// Critical results (including critically failing to defend) double an action's effect.
+    pub fn scale(&self, value: u8) -> u8 {
+        match self {
+            ActionResult::CriticalFail | ActionResult::CriticalSuccess => value.saturating_mul(2),
+            _ => value,
+        }
+    }
+}
+
 impl ActionChoice {
     pub fn evaluate(&self, value: u8) -> (ActionResult, u8) {
         if value < self.fail {
@@ -75,6 +123,11 @@ pub struct Resolution {
     pub roll: u8,
     pub result: ActionResult,
     pub gap: u8,
+    /// Who took the action.
+    pub actor: String,
+    pub pools: Vec<RolledPool>,
+    /// The powers spent on the action's dice before it was rolled.
+    pub powers: Vec<Power>,
 }
 
 #[derive(Component)]
@@ -88,6 +141,9 @@ pub struct PlayerActionBundle {
     pub action_choice: ActionChoice,
     pub action_type: ActionType,
     pub target: ActionTarget,
+    pub targeting: Targeting,
+    pub effects: AppliesEffects,
+    pub outcomes: TextOutcomes,
 }
 
 #[derive(Bundle, Default)]
@@ -96,11 +152,80 @@ pub struct ChallengerActionBundle {
     pub challenger_action: ChallengerAction,
     pub action_type: ActionType,
     pub target: ActionTarget,
+    pub targeting: Targeting,
+    pub effects: AppliesEffects,
+    pub outcomes: TextOutcomes,
 }
 
+/// Everything an action hits. The first target is the one it was aimed at.
 #[derive(Component, InspectorOptions, Reflect, Clone, Debug, Default)]
 #[reflect(InspectorOptions)]
-pub struct ActionTarget(pub Option<Entity>);
+pub struct ActionTarget(pub Vec<Entity>);
+
+impl ActionTarget {
+    pub fn single(entity: Entity) -> Self {
+        Self(vec![entity])
+    }
+
+    pub fn primary(&self) -> Option<Entity> {
+        self.0.first().copied()
+    }
+}
+
+/// How many challengers an action aimed at challengers hits.
+#[derive(Component, InspectorOptions, Reflect, Deserialize, Default, Clone, Copy, Debug)]
+#[reflect(InspectorOptions)]
+pub enum Targeting {
+    #[default]
+    Single,
+    /// The chosen challenger and the ones next to it, up to `count` in total.
+    Cleave {
+        count: usize,
+        #[serde(default)]
+        split_damage: bool,
+    },
+    /// Every remaining challenger, offered as a single action.
+    All {
+        #[serde(default)]
+        split_damage: bool,
+    },
+}
+
+impl Targeting {
+    pub fn offered_once(&self) -> bool {
+        matches!(self, Targeting::All { .. })
+    }
+
+    /// The targets hit when aiming at `remaining[chosen]`, with `remaining` in challenger order.
+    pub fn hits<T: Copy>(&self, remaining: &[T], chosen: usize) -> Vec<T> {
+        match self {
+            Targeting::Single => remaining.get(chosen).copied().into_iter().collect(),
+            Targeting::Cleave { count, .. } => remaining
+                .iter()
+                .cycle()
+                .skip(chosen)
+                .take((*count).min(remaining.len()))
+                .copied()
+                .collect(),
+            Targeting::All { .. } => remaining.to_vec(),
+        }
+    }
+
+    /// The damage the target at `index` out of `hit` targets takes. Split damage
+    /// is shared out evenly, with any remainder going to the first targets.
+    pub fn share(&self, damage: usize, index: usize, hit: usize) -> usize {
+        match self {
+            Targeting::Cleave {
+                split_damage: true, ..
+            }
+            | Targeting::All { split_damage: true } => {
+                let hit = hit.max(1);
+                damage / hit + usize::from(index < damage % hit)
+            }
+            _ => damage,
+        }
+    }
+}
 
 #[derive(Component, InspectorOptions, Reflect, Deserialize, Default, Clone, Debug)]
 #[reflect(InspectorOptions)]
@@ -110,10 +235,60 @@ pub enum ActionType {
     Attack {
         base_damage: u8,
     },
+    Heal {
+        amount: u8,
+    },
+    Defend {
+        reduction: u8,
+    },
+    Flee,
+    Buff {
+        power: Power,
+    },
+}
+
+impl ActionType {
+    /// Whether the action is aimed at a challenger. Other actions affect whoever takes them,
+    /// so players are only offered them once rather than once per challenger.
+    pub fn targets_challenger(&self) -> bool {
+        matches!(self, ActionType::Text | ActionType::Attack { .. })
+    }
+
+    /// Defending resolves before anything else, so it protects against attacks in the same round.
+    pub fn resolution_priority(&self) -> u8 {
+        match self {
+            ActionType::Defend { .. } => 0,
+            _ => 1,
+        }
+    }
+
+    /// The damage dealt to the target for a given result. Challenger actions are
+    /// rolled by the player to defend, so they deal damage when the roll fails.
+    pub fn damage(&self, result: ActionResult, is_challenger: bool) -> u8 {
+        let ActionType::Attack { base_damage } = self else {
+            return 0;
+        };
+        match (is_challenger, result) {
+            (true, ActionResult::CriticalFail) => base_damage.saturating_mul(2),
+            (true, ActionResult::Fail) => *base_damage,
+            (false, ActionResult::Success) => *base_damage,
+            (false, ActionResult::CriticalSuccess) => base_damage.saturating_mul(2),
+            _ => 0,
+        }
+    }
 }
 
 #[derive(Reflect, InspectorOptions, Deserialize, Default, Clone, Debug)]
 pub struct ActionDefinition {
     pub choice: ActionChoice,
     pub action_type: ActionType,
+    #[serde(default)]
+    pub weight: Option<f32>,
+    #[serde(default)]
+    pub targeting: Targeting,
+    #[serde(default)]
+    pub effects: Vec<StatusEffect>,
+    /// What happens for each result of a text action.
+    #[serde(default)]
+    pub outcomes: TextOutcomes,
 }
@@ -0,0 +1,135 @@
+use bevy::{ecs::query::Has, prelude::*};
+use bevy_ui_dsl::*;
+
+use crate::{
+    assets::MainGameAssets,
+    in_game::{
+        encounter::{action_resolutions::ActiveResolution, sequencing::EncounterState},
+        InGameUpdate,
+    },
+    ui::{
+        buttons::{focus_text_button, focused_button_activated, TypedFocusedButtonQuery},
+        classes::*,
+        intermediary_node_bundles::IntoIntermediaryNodeBundle,
+        DisplayBundle,
+    },
+};
+
+use super::{
+    roll_display::{display_roll, hide_until_rolled, RevealAfterRoll},
+    ActionChoice, ActionType, ChallengerAction, Resolution,
+};
+
+pub struct ResolutionScreenPlugin;
+
+impl Plugin for ResolutionScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            InGameUpdate,
+            (focused_button_activated.pipe(process_input))
+                .run_if(in_state(EncounterState::OutcomeResolution)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct Screen;
+
+/// The action that has just started resolving, along with anything `Extra` its type needs.
+pub type CurrentResolutionQuery<'w, 's, 'a, Extra = ()> = Query<
+    'w,
+    's,
+    (
+        &'a ActionChoice,
+        &'a Resolution,
+        &'a ActionType,
+        Option<&'a Parent>,
+        Has<ChallengerAction>,
+        Extra,
+    ),
+    Added<ActiveResolution>,
+>;
+
+#[derive(Component)]
+struct NextButton;
+
+/// Shows how an action went: its title, the dice behind it, and the `lines` describing
+/// its effect, which stay hidden along with the Next button until the dice settle.
+pub fn display_resolution(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    assets: &MainGameAssets,
+    choice: &ActionChoice,
+    resolution: &Resolution,
+    lines: impl FnOnce(&mut UiChildBuilder) -> Vec<Entity>,
+) {
+    let mut reveal = vec![];
+    let mut next_button = None;
+    let root = root(c_root, asset_server, commands, |p| {
+        node(primary_box, p, |p| {
+            node((span.nb(), primary_box_main.nb()), p, |p| {
+                text(&choice.title, (), (main_text, knight_text), p);
+            });
+            display_roll(resolution, assets, p);
+            reveal = lines(p);
+            reveal.push(line(format!("Rolled a {}", resolution.roll), p));
+            focus_text_button(
+                "Next",
+                (c_button.nb(), primary_box_item.nb()),
+                apply_button_state,
+                button_text,
+                p,
+            )
+            .set(&mut next_button);
+        });
+    });
+
+    commands.entity(root).insert(Screen);
+    if let Some(next_button) = next_button {
+        commands.entity(next_button).insert(NextButton);
+        reveal.push(next_button);
+    }
+    hide_until_rolled(commands, &reveal);
+}
+
+/// A line of text on the resolution screen.
+pub fn line(txt: impl Into<String>, p: &mut UiChildBuilder) -> Entity {
+    text(txt, primary_box_item.nb(), standard_text, p)
+}
+
+/// A row of icons on the resolution screen, such as the powers an action granted.
+pub fn icons<'a, T: DisplayBundle + 'a>(
+    items: impl IntoIterator<Item = &'a T>,
+    assets: &MainGameAssets,
+    p: &mut UiChildBuilder,
+) -> Entity {
+    node(primary_box_item.nb(), p, |p| {
+        for item in items {
+            item.display_bundle(assets, 50., p);
+        }
+    })
+}
+
+fn process_input(
+    In(focused): In<Option<Entity>>,
+    mut commands: Commands,
+    interaction_query: TypedFocusedButtonQuery<'_, '_, '_, NextButton>,
+    revealing: Query<(), With<RevealAfterRoll>>,
+    screen: Query<Entity, With<Screen>>,
+    resolved_action: Query<Entity, With<ActiveResolution>>,
+) {
+    let Some(focused) = focused else {
+        return;
+    };
+    // Hidden buttons can still be focused, so wait for the roll to be revealed.
+    if interaction_query.get(focused).is_err() || revealing.contains(focused) {
+        return;
+    }
+    for item in screen.iter() {
+        commands.entity(item).despawn_recursive();
+    }
+
+    for item in resolved_action.iter() {
+        commands.entity(item).despawn_recursive();
+    }
+}
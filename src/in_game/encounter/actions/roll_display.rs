@@ -0,0 +1,139 @@
+use bevy::prelude::*;
+use bevy_ui_dsl::*;
+
+use crate::{
+    assets::MainGameAssets,
+    in_game::{
+        encounter::{
+            dice_pools::{DiceType, RolledDie},
+            sequencing::EncounterState,
+        },
+        InGameUpdate,
+    },
+    ui::{
+        classes::*, colors, intermediary_node_bundles::IntoIntermediaryNodeBundle, DisplayBundle,
+    },
+};
+
+use super::Resolution;
+
+/// How long the first die tumbles for, and how much longer each die after it takes.
+const SETTLE_TIME: f32 = 0.6;
+const SETTLE_STAGGER: f32 = 0.3;
+/// How long a tumbling die shows each face for.
+const TUMBLE_RATE: f32 = 0.06;
+const DIE_SIZE: f32 = 60.;
+
+pub struct RollDisplayPlugin;
+
+impl Plugin for RollDisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            InGameUpdate,
+            (settle_dice, reveal_results)
+                .chain()
+                .run_if(in_state(EncounterState::OutcomeResolution)),
+        );
+    }
+}
+
+/// The value shown over a die, tumbling through its faces until the timer runs out.
+#[derive(Component)]
+struct SettlingDie {
+    die: RolledDie,
+    index: usize,
+    timer: Timer,
+}
+
+/// Hidden until every die on screen has settled.
+#[derive(Component)]
+pub struct RevealAfterRoll;
+
+/// Lays out every die behind a resolution, then any static bonus.
+pub fn display_roll(resolution: &Resolution, assets: &MainGameAssets, p: &mut UiChildBuilder) {
+    node((rolled_dice.nb(), primary_box_item.nb()), p, |p| {
+        let dice = resolution
+            .pools
+            .iter()
+            .flat_map(|pool| pool.result.dice.iter());
+        for (index, die) in dice.enumerate() {
+            node(rolled_die.nb(), p, |p| {
+                die.dice.display_bundle(assets, DIE_SIZE, p);
+                node(rolled_die_value.nb(), p, |p| {
+                    p.spawn((
+                        TextBundle::from_section(
+                            "",
+                            TextStyle {
+                                font: assets.druids_font.clone(),
+                                font_size: DIE_SIZE * 0.5,
+                                color: colors::PRIMARY_COLOR,
+                            },
+                        ),
+                        SettlingDie {
+                            die: *die,
+                            index,
+                            timer: Timer::from_seconds(
+                                SETTLE_TIME + SETTLE_STAGGER * index as f32,
+                                TimerMode::Once,
+                            ),
+                        },
+                    ));
+                });
+            });
+        }
+        let bonus = resolution
+            .pools
+            .iter()
+            .fold(0u8, |bonus, pool| bonus.saturating_add(pool.result.bonus));
+        if bonus > 0 {
+            DiceType::Static { value: bonus }.display_bundle(assets, DIE_SIZE, p);
+        }
+    });
+}
+
+pub fn hide_until_rolled(commands: &mut Commands, entities: &[Entity]) {
+    for entity in entities.iter() {
+        commands
+            .entity(*entity)
+            .insert((RevealAfterRoll, Visibility::Hidden));
+    }
+}
+
+fn settle_dice(time: Res<Time>, mut dice: Query<(&mut SettlingDie, &mut Text)>) {
+    for (mut settling, mut text) in dice.iter_mut() {
+        if settling.timer.finished() {
+            continue;
+        }
+        settling.timer.tick(time.delta());
+        let Some(section) = text.sections.get_mut(0) else {
+            continue;
+        };
+        if settling.timer.finished() {
+            section.value = settling.die.value.to_string();
+            // Dice that were rolled but dropped, like the lower die with advantage, fade out.
+            section.style.color = if settling.die.kept {
+                colors::PRIMARY_COLOR
+            } else {
+                colors::FAIL_COLOR
+            };
+        } else {
+            let faces = settling.die.dice.faces().unwrap_or(1) as usize;
+            let tumble = (settling.timer.elapsed_secs() / TUMBLE_RATE) as usize;
+            section.value = (1 + (tumble * 5 + settling.index) % faces).to_string();
+        }
+    }
+}
+
+fn reveal_results(
+    mut commands: Commands,
+    dice: Query<&SettlingDie>,
+    mut hidden: Query<(Entity, &mut Visibility), With<RevealAfterRoll>>,
+) {
+    if dice.iter().any(|settling| !settling.timer.finished()) {
+        return;
+    }
+    for (entity, mut visibility) in hidden.iter_mut() {
+        *visibility = Visibility::Inherited;
+        commands.entity(entity).remove::<RevealAfterRoll>();
+    }
+}
@@ -1,106 +1,237 @@
 use bevy::prelude::*;
-use bevy_ui_dsl::*;
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use serde::Deserialize;
 
 use crate::{
+    assets::MainGameAssets,
     in_game::{
-        encounter::{action_resolutions::ActiveResolution, sequencing::EncounterState},
-        InGameUpdate,
-    },
-    ui::{
-        buttons::{focus_text_button, focused_button_activated},
-        classes::*,
-        intermediary_node_bundles::IntoIntermediaryNodeBundle,
+        encounter::{
+            challenger::Challenger,
+            encounter_log::EncounterLog,
+            encounter_resolution::ChallengerCompleted,
+            encounter_setup_types::EncounterSetup,
+            health::{CurrentHealth, MaxHealth},
+            powers::Power,
+            sequencing::EncounterState,
+        },
+        factions::Faction,
+        grant_power,
+        mission::mission_types::MissionStage,
     },
 };
 
-use super::{ActionChoice, ActionType, Resolution};
+use super::{
+    resolution_screen::{display_resolution, icons, line, CurrentResolutionQuery},
+    ActionDefinition, ActionResult, ActionTarget, ActionType, TemporaryPower,
+};
 
 pub struct TextActionPlugin;
 
 impl Plugin for TextActionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            display_text_resolution.run_if(in_state(EncounterState::OutcomeResolution)),
-        )
-        .add_systems(
-            InGameUpdate,
-            (focused_button_activated.pipe(process_input))
-                .run_if(in_state(EncounterState::OutcomeResolution)),
-        );
+        app.register_type::<TextOutcomes>()
+            .register_type::<TextOutcome>()
+            .register_type::<Consequence>()
+            .add_systems(
+                Update,
+                display_text_resolution.run_if(in_state(EncounterState::OutcomeResolution)),
+            );
     }
 }
-#[derive(Component)]
-struct Screen;
 
-#[derive(Component)]
-pub struct Button;
+/// Something that happens when a text action resolves. The challenger is the one the
+/// action targets, or the one taking it for challenger actions.
+#[derive(InspectorOptions, Reflect, Deserialize, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub enum Consequence {
+    /// Heals the player when positive, damages them when negative.
+    PlayerHealth(i8),
+    /// Heals the challenger when positive, damages them when negative.
+    ChallengerHealth(i8),
+    GrantPower(Power),
+    RemovePower(Power),
+    /// Ends the encounter for the challenger, such as talking them down.
+    CompleteChallenger,
+    /// Offers the player a new action against the challenger from the next round.
+    FollowUp(ActionDefinition),
+}
+
+#[derive(InspectorOptions, Reflect, Deserialize, Default, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub struct TextOutcome {
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub consequences: Vec<Consequence>,
+}
+
+/// The outcome of a text action for each result. Results without one just show how the roll went.
+#[derive(Component, InspectorOptions, Reflect, Deserialize, Default, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub struct TextOutcomes {
+    #[serde(default)]
+    pub critical_fail: Option<TextOutcome>,
+    #[serde(default)]
+    pub fail: Option<TextOutcome>,
+    #[serde(default)]
+    pub success: Option<TextOutcome>,
+    #[serde(default)]
+    pub critical_success: Option<TextOutcome>,
+}
+
+impl TextOutcomes {
+    pub fn get(&self, result: ActionResult) -> Option<&TextOutcome> {
+        match result {
+            ActionResult::CriticalFail => self.critical_fail.as_ref(),
+            ActionResult::Fail => self.fail.as_ref(),
+            ActionResult::Success => self.success.as_ref(),
+            ActionResult::CriticalSuccess => self.critical_success.as_ref(),
+        }
+    }
+
+    /// Replaces `**` with the challenger's name, as is done for action titles.
+    pub fn named(&self, name: &str) -> Self {
+        let named = |outcome: &Option<TextOutcome>| {
+            outcome.as_ref().map(|outcome| TextOutcome {
+                text: outcome.text.as_ref().map(|t| t.replace("**", name)),
+                consequences: outcome.consequences.clone(),
+            })
+        };
+        Self {
+            critical_fail: named(&self.critical_fail),
+            fail: named(&self.fail),
+            success: named(&self.success),
+            critical_success: named(&self.critical_success),
+        }
+    }
+}
 
+#[allow(clippy::too_many_arguments)]
 fn display_text_resolution(
     mut commands: Commands,
-    resolution: Query<(Entity, &ActionChoice, &Resolution, &ActionType), Added<ActiveResolution>>,
+    resolution: CurrentResolutionQuery<'_, '_, '_, (&TextOutcomes, &ActionTarget)>,
+    mut healths: Query<(&mut CurrentHealth, &MaxHealth)>,
+    mut challengers: Query<&mut Challenger>,
+    powers: Query<(Entity, &Power, &Faction), Without<TemporaryPower>>,
+    setup: Res<EncounterSetup>,
+    mission_stage: Option<ResMut<MissionStage>>,
+    mut log: ResMut<EncounterLog>,
     asset_server: Res<AssetServer>,
+    assets: Res<MainGameAssets>,
 ) {
-    let Ok((_entity, choice, resolution, action_type)) = resolution.get_single() else {
+    let Ok((choice, resolution, action_type, actor, is_challenger, (outcomes, target))) =
+        resolution.get_single()
+    else {
         return;
     };
     if !matches!(action_type, ActionType::Text) {
         return;
     }
-    let mut next_button = None;
-    let root = root(c_root, &asset_server, &mut commands, |p| {
-        node(primary_box, p, |p| {
-            node((span.nb(), primary_box_main.nb()), p, |p| {
-                text(&choice.title, (), (main_text, knight_text), p);
-            });
-            text(
-                match resolution.result {
-                    super::ActionResult::CriticalFail => "Failed Badly",
-                    super::ActionResult::Fail => "Failed",
-                    super::ActionResult::Success => "Succeeded!",
-                    super::ActionResult::CriticalSuccess => "Amazing Success!",
-                },
-                primary_box_item.nb(),
-                standard_text,
-                p,
-            );
-            text(
-                format!("Rolled a {}", resolution.roll),
-                primary_box_item.nb(),
-                standard_text,
-                p,
-            );
-            focus_text_button(
-                "Next",
-                (c_button.nb(), primary_box_item.nb()),
-                apply_button_state,
-                button_text,
-                p,
-            )
-            .set(&mut next_button);
-        });
-    });
-
-    commands.entity(root).insert(Screen);
-    if let Some(next_button) = next_button {
-        commands.entity(next_button).insert(Button);
+    if let (Some(mut stage), false) = (mission_stage, is_challenger) {
+        stage
+            .results
+            .push((choice.title.clone(), resolution.result));
     }
-}
-
-fn process_input(
-    In(focused): In<Option<Entity>>,
-    mut commands: Commands,
-    screen: Query<Entity, With<Screen>>,
-    resolved_action: Query<Entity, With<ActiveResolution>>,
-) {
-    let Some(_) = focused else {
-        return;
+    let actor = actor.map(|p| p.get());
+    let (player, challenger) = if is_challenger {
+        (target.primary(), actor)
+    } else {
+        (actor, target.primary())
     };
-    for item in screen.iter() {
-        commands.entity(item).despawn_recursive();
-    }
 
-    for item in resolved_action.iter() {
-        commands.entity(item).despawn_recursive();
+    let outcome = outcomes.get(resolution.result);
+    let mut messages = vec![];
+    let mut gained = vec![];
+    let mut lost = vec![];
+    let mut removed = vec![];
+    let mut damage = 0;
+    for consequence in outcome.iter().flat_map(|o| o.consequences.iter()) {
+        match consequence {
+            Consequence::PlayerHealth(change) | Consequence::ChallengerHealth(change) => {
+                let is_player = matches!(consequence, Consequence::PlayerHealth(_));
+                let entity = if is_player { player } else { challenger };
+                let Some(Ok((mut current, max))) = entity.map(|e| healths.get_mut(e)) else {
+                    continue;
+                };
+                let before = current.0;
+                current.0 = current.0.saturating_add_signed(*change as isize).min(max.0);
+                let who = if is_player { "Player" } else { "Target" };
+                if current.0 < before {
+                    damage += before - current.0;
+                    messages.push(format!("{who} took {}", before - current.0));
+                } else if current.0 > before {
+                    messages.push(format!("{who} recovered {}", current.0 - before));
+                }
+            }
+            Consequence::GrantPower(power) => {
+                grant_power(&mut commands, *power, setup.player_faction);
+                gained.push(*power);
+            }
+            Consequence::RemovePower(power) => {
+                let Some((entity, _, _)) = powers.iter().find(|(entity, p, faction)| {
+                    *p == power && **faction == setup.player_faction && !removed.contains(entity)
+                }) else {
+                    continue;
+                };
+                commands.entity(entity).despawn_recursive();
+                removed.push(entity);
+                lost.push(*power);
+            }
+            Consequence::CompleteChallenger => {
+                if let Some(challenger) = challenger {
+                    commands.entity(challenger).insert(ChallengerCompleted);
+                }
+            }
+            Consequence::FollowUp(action) => {
+                let Some(Ok(mut challenger)) = challenger.map(|e| challengers.get_mut(e)) else {
+                    continue;
+                };
+                if !challenger
+                    .published_actions
+                    .iter()
+                    .any(|a| a.choice.title == action.choice.title)
+                {
+                    challenger.published_actions.push(action.clone());
+                    messages.push(format!(
+                        "New option: {}",
+                        action.choice.title.replace("**", &challenger.name)
+                    ));
+                }
+            }
+        }
     }
+
+    log.record(&choice.title, resolution, (damage > 0).then_some(damage));
+    display_resolution(
+        &mut commands,
+        &asset_server,
+        &assets,
+        choice,
+        resolution,
+        |p| {
+            let mut lines = vec![line(
+                match resolution.result {
+                    ActionResult::CriticalFail => "Failed Badly",
+                    ActionResult::Fail => "Failed",
+                    ActionResult::Success => "Succeeded!",
+                    ActionResult::CriticalSuccess => "Amazing Success!",
+                },
+                p,
+            )];
+            if let Some(narrative) = outcome.and_then(|o| o.text.as_ref()) {
+                lines.push(line(narrative, p));
+            }
+            for message in messages.iter() {
+                lines.push(line(message, p));
+            }
+            for (label, powers) in [("Gained:", &gained), ("Lost:", &lost)] {
+                if powers.is_empty() {
+                    continue;
+                }
+                lines.push(line(label, p));
+                lines.push(icons(powers.iter(), &assets, p));
+            }
+            lines
+        },
+    );
 }
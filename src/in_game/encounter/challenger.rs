@@ -5,19 +5,31 @@ use bevy::{
 };
 use bevy_common_assets::yaml::YamlAssetPlugin;
 use bevy_inspector_egui::InspectorOptions;
-use bevy_turborand::{DelegatedRng, GlobalRng, TurboRand};
+use bevy_turborand::DelegatedRng;
 use serde::Deserialize;
 
-use crate::materialized_scene::MaterializedSceneReference;
+use crate::{
+    in_game::{
+        difficulty::Difficulty,
+        endless::{effective_difficulty, EndlessRun},
+        mission::mission_types::MissionStage,
+        run_rng::{checkpoint, RngStream, RunRng},
+        story::PhaseRound,
+    },
+    materialized_scene::MaterializedSceneReference,
+};
 
 use super::{
     actions::{
         ActionChoice, ActionDefinition, ActionTarget, ChallengerActionBundle, PlayerActionBundle,
     },
+    challenger_strategy::{ChallengerDecision, ChallengerStrategy, DecisionContext},
     encounter_resolution::ChallengerCompleted,
-    health::MaxHealth,
+    health::{CurrentHealth, MaxHealth},
+    initiative::Initiative,
     player::Player,
     sequencing::{EncounterState, PublishAvailableActions},
+    status_effects::{AppliesEffects, StatusEffects},
 };
 
 pub struct ChallengerPlugin;
@@ -25,6 +37,7 @@ pub struct ChallengerPlugin;
 impl Plugin for ChallengerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.register_type::<ChallengerReference>()
+            .register_type::<ChallengerStrategy>()
             .register_type::<Challenger>()
             .register_type::<Challengers>()
             .add_plugins(YamlAssetPlugin::<Challengers>::new(&["ch.yaml"]))
@@ -42,6 +55,22 @@ pub struct ChallengerReference {
     pub available_actions: Vec<ActionDefinition>,
     pub published_actions: Vec<ActionDefinition>,
     pub health: Option<MaxHealth>,
+    #[serde(default)]
+    pub imposes_disadvantage: bool,
+    #[serde(default)]
+    pub strategy: ChallengerStrategy,
+    #[serde(default)]
+    pub initiative: Initiative,
+    /// Experience the hero earns for defeating this challenger.
+    #[serde(default)]
+    pub xp: usize,
+    /// How much of a generated encounter's budget this challenger uses up.
+    /// Challengers without a threat are never generated.
+    #[serde(default)]
+    pub threat: usize,
+    /// Matched against challenger pools and location tags when generating encounters.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Reflect, InspectorOptions, Component)]
@@ -50,6 +79,9 @@ pub struct Challenger {
     pub name: String,
     pub available_actions: Vec<ActionDefinition>,
     pub published_actions: Vec<ActionDefinition>,
+    pub imposes_disadvantage: bool,
+    pub strategy: ChallengerStrategy,
+    pub xp: usize,
 }
 
 #[derive(Reflect, InspectorOptions, Deserialize, TypeUuid)]
@@ -60,44 +92,95 @@ impl Challengers {
     pub fn get(&self, key: &str) -> Option<&ChallengerReference> {
         self.0.get(key)
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ChallengerReference)> {
+        self.0.iter()
+    }
 }
 
+type ChallengerHealthQuery<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'a Challenger,
+        Option<&'a CurrentHealth>,
+        Option<&'a MaxHealth>,
+        Option<&'a StatusEffects>,
+    ),
+    Without<ChallengerCompleted>,
+>;
+
+#[allow(clippy::too_many_arguments)]
 fn publish_challenger_action(
     mut commands: Commands,
-    challengers: Query<(Entity, &Challenger), Without<ChallengerCompleted>>,
-    players: Query<Entity, With<Player>>,
-    mut global_rng: ResMut<GlobalRng>,
+    challengers: ChallengerHealthQuery,
+    players: Query<(Entity, &CurrentHealth, &MaxHealth), With<Player>>,
+    mut run_rng: ResMut<RunRng>,
+    phase_round: Res<PhaseRound>,
+    mission_stage: Option<Res<MissionStage>>,
+    difficulty: Res<Difficulty>,
+    endless: Option<Res<EndlessRun>>,
 ) {
-    let rng = global_rng.get_mut();
+    let difficulty = effective_difficulty(&difficulty, endless.as_deref());
+    let rng = run_rng
+        .stream(
+            RngStream::Challengers,
+            &checkpoint(&phase_round, mission_stage.as_deref()),
+        )
+        .get_mut();
+    let player_health = players
+        .iter()
+        .next()
+        .map(|(_, current, max)| (current.0, max.0));
 
-    for (entity, challenger) in challengers.iter() {
-        let Some(choice) = rng.sample(&challenger.available_actions) else {
-            continue;
+    for (entity, challenger, current, max, status) in challengers.iter() {
+        // A stunned challenger loses its own action, but the player can still act on it.
+        let stunned = status.map(|s| s.is_stunned()).unwrap_or(false);
+        let context = DecisionContext {
+            own_health: current.zip(max).map(|(current, max)| (current.0, max.0)),
+            player_health,
+        };
+        let choice = if stunned {
+            None
+        } else {
+            challenger
+                .strategy
+                .choose(&challenger.available_actions, &context, rng)
         };
 
-        for player in players.iter() {
-            commands.entity(entity).with_children(|p| {
-                p.spawn(ChallengerActionBundle {
-                    action_choice: ActionChoice {
-                        title: choice.choice.title.replace("**", &challenger.name),
-                        content: choice.choice.content.replace("**", &challenger.name),
-                        ..choice.choice.clone()
-                    },
-                    action_type: choice.action_type.clone(),
-                    target: ActionTarget(Some(player)),
-                    ..default()
+        for (player, _, _) in players.iter() {
+            if let Some(choice) = choice {
+                commands.entity(entity).with_children(|p| {
+                    p.spawn(ChallengerActionBundle {
+                        action_choice: difficulty.choice(ActionChoice {
+                            title: choice.choice.title.replace("**", &challenger.name),
+                            content: choice.choice.content.replace("**", &challenger.name),
+                            ..choice.choice.clone()
+                        }),
+                        action_type: difficulty.action_type(&choice.action_type),
+                        target: ActionTarget::single(player),
+                        effects: AppliesEffects(choice.effects.clone()),
+                        outcomes: choice.outcomes.named(&challenger.name),
+                        ..default()
+                    });
                 });
-            });
+            }
             commands.entity(player).with_children(|p| {
                 for choice in challenger.published_actions.iter() {
+                    // Challenger health already scales, so only the thresholds of
+                    // actions against it do.
                     p.spawn(PlayerActionBundle {
-                        action_choice: ActionChoice {
+                        action_choice: difficulty.choice(ActionChoice {
                             title: choice.choice.title.replace("**", &challenger.name),
                             content: choice.choice.content.replace("**", &challenger.name),
                             ..choice.choice.clone()
-                        },
+                        }),
                         action_type: choice.action_type.clone(),
-                        target: ActionTarget(Some(entity)),
+                        target: ActionTarget::single(entity),
+                        effects: AppliesEffects(choice.effects.clone()),
+                        outcomes: choice.outcomes.named(&challenger.name),
+                        ..default()
                     });
                 }
             });
@@ -0,0 +1,169 @@
+use bevy::{prelude::*, reflect::Reflect};
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use bevy_turborand::TurboRand;
+use serde::Deserialize;
+
+use super::{
+    actions::{ActionDefinition, ActionType},
+    dice_distribution::Distribution,
+};
+
+/// How a challenger picks which of its `available_actions` to use each round.
+#[derive(InspectorOptions, Reflect, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[reflect(InspectorOptions)]
+pub enum ChallengerStrategy {
+    #[default]
+    Random,
+    Weighted,
+    Utility,
+}
+
+/// What a challenger knows about the encounter when choosing an action.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DecisionContext {
+    pub own_health: Option<(usize, usize)>,
+    pub player_health: Option<(usize, usize)>,
+}
+
+impl DecisionContext {
+    fn own_health_ratio(&self) -> f32 {
+        health_ratio(self.own_health)
+    }
+}
+
+fn health_ratio(health: Option<(usize, usize)>) -> f32 {
+    match health {
+        Some((current, max)) if max > 0 => current as f32 / max as f32,
+        _ => 1.,
+    }
+}
+
+pub trait ChallengerDecision {
+    fn choose<'a>(
+        &self,
+        actions: &'a [ActionDefinition],
+        context: &DecisionContext,
+        rng: &mut impl TurboRand,
+    ) -> Option<&'a ActionDefinition>;
+}
+
+impl ChallengerDecision for ChallengerStrategy {
+    fn choose<'a>(
+        &self,
+        actions: &'a [ActionDefinition],
+        context: &DecisionContext,
+        rng: &mut impl TurboRand,
+    ) -> Option<&'a ActionDefinition> {
+        match self {
+            ChallengerStrategy::Random => RandomDecision.choose(actions, context, rng),
+            ChallengerStrategy::Weighted => WeightedDecision.choose(actions, context, rng),
+            ChallengerStrategy::Utility => UtilityDecision.choose(actions, context, rng),
+        }
+    }
+}
+
+/// Picks any available action with equal likelihood.
+pub struct RandomDecision;
+
+impl ChallengerDecision for RandomDecision {
+    fn choose<'a>(
+        &self,
+        actions: &'a [ActionDefinition],
+        _context: &DecisionContext,
+        rng: &mut impl TurboRand,
+    ) -> Option<&'a ActionDefinition> {
+        rng.sample(actions)
+    }
+}
+
+/// Picks actions in proportion to their authored `weight`, defaulting to 1.
+pub struct WeightedDecision;
+
+impl ChallengerDecision for WeightedDecision {
+    fn choose<'a>(
+        &self,
+        actions: &'a [ActionDefinition],
+        _context: &DecisionContext,
+        rng: &mut impl TurboRand,
+    ) -> Option<&'a ActionDefinition> {
+        let weight = |action: &ActionDefinition| action.weight.unwrap_or(1.).max(0.);
+        let total: f32 = actions.iter().map(weight).sum();
+        if total <= 0. {
+            return rng.sample(actions);
+        }
+        let mut target = rng.f32() * total;
+        for action in actions.iter() {
+            target -= weight(action);
+            if target <= 0. {
+                return Some(action);
+            }
+        }
+        actions.last()
+    }
+}
+
+/// Picks the action with the highest expected value, based on the exact
+/// distribution of its dice pool against its thresholds.
+pub struct UtilityDecision;
+
+/// How much a chance to defeat the player outweighs raw expected damage.
+const LETHAL_WEIGHT: f32 = 10.;
+
+/// Below this ratio of its health, a challenger values protecting itself.
+const LOW_HEALTH: f32 = 0.3;
+
+impl UtilityDecision {
+    pub fn score(action: &ActionDefinition, context: &DecisionContext) -> f32 {
+        let pools = action.choice.dice_pool.iter().collect::<Vec<_>>();
+        let distribution = pools.as_slice().distribution();
+
+        let (expected_damage, lethal_chance) =
+            distribution
+                .iter()
+                .fold((0., 0.), |(expected, lethal), (value, probability)| {
+                    let (result, _) = action.choice.evaluate(value);
+                    let damage = action.action_type.damage(result, true) as f64;
+                    let is_lethal = context
+                        .player_health
+                        .map(|(current, _)| damage > 0. && damage as usize >= current)
+                        .unwrap_or(false);
+                    (
+                        expected + damage * probability,
+                        if is_lethal {
+                            lethal + probability
+                        } else {
+                            lethal
+                        },
+                    )
+                });
+
+        let offense = expected_damage as f32 + lethal_chance as f32 * LETHAL_WEIGHT;
+
+        match action.action_type {
+            ActionType::Attack { .. } => offense,
+            _ => {
+                if context.own_health_ratio() < LOW_HEALTH {
+                    1. - context.own_health_ratio()
+                } else {
+                    0.
+                }
+            }
+        }
+    }
+}
+
+impl ChallengerDecision for UtilityDecision {
+    fn choose<'a>(
+        &self,
+        actions: &'a [ActionDefinition],
+        context: &DecisionContext,
+        rng: &mut impl TurboRand,
+    ) -> Option<&'a ActionDefinition> {
+        actions
+            .iter()
+            // A little noise breaks ties between equally good actions.
+            .map(|action| (action, Self::score(action, context) + rng.f32() * 0.01))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(action, _)| action)
+    }
+}
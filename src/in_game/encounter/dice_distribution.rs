@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use super::dice_pools::{DicePool, DicePoolType, DiceType, MAX_EXPLOSIONS};
+
+/// An exact probability mass function over roll totals.
+///
+/// The probability of rolling `value` is stored at index `value`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pmf(Vec<f64>);
+
+impl Pmf {
+    pub fn certain(value: u8) -> Self {
+        let mut probabilities = vec![0.; value as usize + 1];
+        probabilities[value as usize] = 1.;
+        Self(probabilities)
+    }
+
+    pub fn uniform(min: u8, max: u8) -> Self {
+        let (min, max) = (min.min(max) as usize, min.max(max) as usize);
+        let chance = 1. / ((max - min + 1) as f64);
+        let mut probabilities = vec![0.; max + 1];
+        for probability in probabilities.iter_mut().skip(min) {
+            *probability = chance;
+        }
+        Self(probabilities)
+    }
+
+    /// Iterates over every total with a non-zero probability, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = (u8, f64)> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| **p > 0.)
+            .map(|(v, p)| (u8::try_from(v).unwrap_or(u8::MAX), *p))
+    }
+
+    /// The highest total that can come up.
+    pub fn highest(&self) -> u8 {
+        self.iter()
+            .last()
+            .map(|(value, _)| value)
+            .unwrap_or_default()
+    }
+
+    /// The distribution of the sum of one roll from each.
+    pub fn convolve(&self, other: &Pmf) -> Pmf {
+        let mut probabilities = vec![0.; self.0.len() + other.0.len() - 1];
+        for (a, pa) in self.0.iter().enumerate().filter(|(_, p)| **p > 0.) {
+            for (b, pb) in other.0.iter().enumerate().filter(|(_, p)| **p > 0.) {
+                probabilities[a + b] += pa * pb;
+            }
+        }
+        Self(probabilities)
+    }
+
+    /// The distribution of rolling `rolled` times and summing the highest
+    /// (or lowest) `kept` results.
+    pub fn keep(&self, rolled: u8, kept: u8, highest: bool) -> Pmf {
+        let kept = kept.min(rolled) as usize;
+        // Track the sorted kept values so far, starting with no dice rolled.
+        let mut states: BTreeMap<Vec<u8>, f64> = BTreeMap::from([(vec![], 1.)]);
+        for _ in 0..rolled {
+            let mut next_states = BTreeMap::new();
+            for (kept_values, chance) in states.iter() {
+                for (value, probability) in self.iter() {
+                    let mut next = kept_values.clone();
+                    next.push(value);
+                    next.sort();
+                    if next.len() > kept {
+                        if highest {
+                            next.remove(0);
+                        } else {
+                            next.pop();
+                        }
+                    }
+                    *next_states.entry(next).or_insert(0.) += chance * probability;
+                }
+            }
+            states = next_states;
+        }
+
+        let mut probabilities = vec![];
+        for (kept_values, chance) in states {
+            let total = kept_values.iter().map(|v| *v as usize).sum::<usize>();
+            if probabilities.len() <= total {
+                probabilities.resize(total + 1, 0.);
+            }
+            probabilities[total] += chance;
+        }
+        Self(probabilities)
+    }
+
+    /// Re-rolls once whenever the lowest possible total comes up.
+    pub fn reroll_lowest(&self) -> Pmf {
+        let Some((lowest, chance)) = self.iter().next() else {
+            return self.clone();
+        };
+        let mut probabilities: Vec<f64> = self.0.iter().map(|p| p * chance).collect();
+        for (value, probability) in probabilities.iter_mut().enumerate() {
+            if value != lowest as usize {
+                *probability += self.0[value];
+            }
+        }
+        Self(probabilities)
+    }
+
+    /// Adds another roll whenever the highest possible total comes up, up to `depth` times.
+    pub fn explode(&self, depth: usize) -> Pmf {
+        let Some((highest, chance)) = self.iter().last() else {
+            return self.clone();
+        };
+        if depth == 0 || chance >= 1. {
+            return self.clone();
+        }
+        let mut probabilities = self.0.clone();
+        probabilities[highest as usize] = 0.;
+        let exploded = self.explode(depth - 1).convolve(&Pmf::certain(highest));
+        probabilities.resize(probabilities.len().max(exploded.0.len()), 0.);
+        for (value, probability) in exploded.0.iter().enumerate() {
+            probabilities[value] += probability * chance;
+        }
+        Self(probabilities)
+    }
+
+    /// Scales the distribution so the most likely total is `1.`, for drawing bars.
+    pub fn relative(&self) -> Vec<(u8, f32)> {
+        let max = self.0.iter().copied().fold(0., f64::max);
+        if max <= 0. {
+            return vec![];
+        }
+        self.iter().map(|(v, p)| (v, (p / max) as f32)).collect()
+    }
+}
+
+pub trait Distribution {
+    fn distribution(&self) -> Pmf;
+}
+
+impl Distribution for DiceType {
+    fn distribution(&self) -> Pmf {
+        match self {
+            DiceType::Static { value } => Pmf::certain(*value),
+            DiceType::D2 => Pmf::uniform(1, 2),
+            DiceType::D3 => Pmf::uniform(1, 3),
+            DiceType::D4 => Pmf::uniform(1, 4),
+            DiceType::D6 => Pmf::uniform(1, 6),
+            DiceType::D8 => Pmf::uniform(1, 8),
+            DiceType::D12 => Pmf::uniform(1, 12),
+        }
+    }
+}
+
+impl Distribution for DicePool {
+    fn distribution(&self) -> Pmf {
+        let dice = self.dice.distribution();
+        match self.pool {
+            DicePoolType::Single => dice,
+            DicePoolType::Advantage
+            | DicePoolType::Disadvantage
+            | DicePoolType::KeepHighest { .. }
+            | DicePoolType::KeepLowest { .. } => match self.pool.keep() {
+                Some(keep) => dice.keep(keep.rolled, keep.kept, keep.highest),
+                None => dice,
+            },
+            DicePoolType::Exploding => dice.explode(MAX_EXPLOSIONS),
+            DicePoolType::ReRoll => dice.reroll_lowest(),
+            DicePoolType::PreRolled(value) => Pmf::certain(value),
+        }
+    }
+}
+
+impl<T: Distribution> Distribution for &[&T] {
+    fn distribution(&self) -> Pmf {
+        if self.is_empty() {
+            Pmf::certain(1)
+        } else {
+            self.iter()
+                .map(|v| v.distribution())
+                .fold(Pmf::certain(0), |total, next| total.convolve(&next))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chance(pmf: &Pmf, value: usize) -> f64 {
+        pmf.0.get(value).copied().unwrap_or_default()
+    }
+
+    fn assert_chance(pmf: &Pmf, value: usize, expected: f64) {
+        let actual = chance(pmf, value);
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{value}: expected {expected}, got {actual}"
+        );
+    }
+
+    fn assert_total(pmf: &Pmf) {
+        assert!((pmf.0.iter().sum::<f64>() - 1.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn two_d6_sum_to_seven_most_often() {
+        let d6 = DiceType::D6.distribution();
+        let two_d6 = d6.convolve(&d6);
+        assert_chance(&two_d6, 7, 6. / 36.);
+        assert_chance(&two_d6, 2, 1. / 36.);
+        assert_chance(&two_d6, 12, 1. / 36.);
+        assert_total(&two_d6);
+    }
+
+    #[test]
+    fn static_bonus_shifts_the_roll() {
+        let pmf = DiceType::D6
+            .distribution()
+            .convolve(&DiceType::Static { value: 2 }.distribution());
+        assert_chance(&pmf, 2, 0.);
+        assert_chance(&pmf, 3, 1. / 6.);
+        assert_chance(&pmf, 8, 1. / 6.);
+        assert_total(&pmf);
+    }
+
+    #[test]
+    fn advantage_and_disadvantage() {
+        let advantage = DicePool::d6().advantage().distribution();
+        assert_chance(&advantage, 6, 11. / 36.);
+        assert_chance(&advantage, 1, 1. / 36.);
+        assert_total(&advantage);
+
+        let disadvantage = DicePool::d6().disadvantage().distribution();
+        assert_chance(&disadvantage, 1, 11. / 36.);
+        assert_chance(&disadvantage, 6, 1. / 36.);
+        assert_total(&disadvantage);
+    }
+
+    #[test]
+    fn keep_highest_two_of_three() {
+        let pmf = DiceType::D6.distribution().keep(3, 2, true);
+        // At least two sixes: three ways to get exactly two, plus all three.
+        assert_chance(&pmf, 12, 16. / 216.);
+        assert_chance(&pmf, 2, 1. / 216.);
+        assert_total(&pmf);
+    }
+
+    #[test]
+    fn keep_lowest_two_of_three() {
+        let pmf = DiceType::D6.distribution().keep(3, 2, false);
+        assert_chance(&pmf, 2, 16. / 216.);
+        assert_chance(&pmf, 12, 1. / 216.);
+        assert_total(&pmf);
+    }
+
+    #[test]
+    fn reroll_lowest_rerolls_ones_once() {
+        let pmf = DiceType::D6.distribution().reroll_lowest();
+        assert_chance(&pmf, 1, 1. / 36.);
+        assert_chance(&pmf, 4, 7. / 36.);
+        assert_total(&pmf);
+    }
+
+    #[test]
+    fn explode_adds_a_roll_on_the_highest_face() {
+        let pmf = DiceType::D6.distribution().explode(1);
+        assert_chance(&pmf, 5, 1. / 6.);
+        // A six always explodes, so it can't be the total.
+        assert_chance(&pmf, 6, 0.);
+        assert_chance(&pmf, 7, 1. / 36.);
+        assert_chance(&pmf, 12, 1. / 36.);
+        assert_total(&pmf);
+
+        let deep = DicePool::d4().exploding().distribution();
+        assert_chance(&deep, 16, 1. / 256.);
+        assert_total(&deep);
+    }
+
+    #[test]
+    fn pre_rolled_is_certain() {
+        let pmf = DicePool::d6().pre_rolled(4).distribution();
+        assert_chance(&pmf, 4, 1.);
+        assert_total(&pmf);
+    }
+
+    #[test]
+    fn empty_pool_rolls_one() {
+        let pools: &[&DicePool] = &[];
+        assert_chance(&pools.distribution(), 1, 1.);
+    }
+}
@@ -2,12 +2,14 @@ use bevy::{prelude::*, reflect::Reflect};
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
 use bevy_turborand::TurboRand;
 use bevy_ui_dsl::{node, UiChildBuilder};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     assets::MainGameAssets,
     ui::{
-        classes::dice_pool_modifier, colors, intermediary_node_bundles::IntoIntermediaryNodeBundle,
+        classes::{dice_pool_modifier, pre_rolled_value},
+        colors,
+        intermediary_node_bundles::IntoIntermediaryNodeBundle,
         spawn_icon, DisplayBundle,
     },
 };
@@ -15,10 +17,64 @@ use crate::{
 use super::powers::Power;
 
 pub trait Roll {
-    fn roll(&self, rng: &mut impl TurboRand) -> u8;
+    fn roll(&self, rng: &mut impl TurboRand) -> RollResult;
 }
 
-#[derive(InspectorOptions, Reflect, Clone, Debug, Copy, PartialEq, Eq, Deserialize)]
+/// A single die from a roll. Dice that were rolled but not kept don't count towards the total.
+#[derive(InspectorOptions, Reflect, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[reflect(InspectorOptions)]
+pub struct RolledDie {
+    pub dice: DiceType,
+    pub value: u8,
+    pub kept: bool,
+}
+
+#[derive(
+    InspectorOptions, Reflect, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[reflect(InspectorOptions)]
+pub struct RollResult {
+    pub dice: Vec<RolledDie>,
+    /// Static bonuses added on top of the dice.
+    pub bonus: u8,
+    pub total: u8,
+}
+
+impl RollResult {
+    /// Totals the kept dice, treating static dice as a bonus rather than a die.
+    fn new(dice: DiceType, rolls: Vec<(u8, bool)>) -> Self {
+        let kept = rolls
+            .iter()
+            .filter(|(_, kept)| *kept)
+            .fold(0u8, |total, (value, _)| total.saturating_add(*value));
+        if let DiceType::Static { .. } = dice {
+            return Self {
+                dice: vec![],
+                bonus: kept,
+                total: kept,
+            };
+        }
+        Self {
+            dice: rolls
+                .into_iter()
+                .map(|(value, kept)| RolledDie { dice, value, kept })
+                .collect(),
+            bonus: 0,
+            total: kept,
+        }
+    }
+
+    fn combine(results: impl Iterator<Item = RollResult>) -> Self {
+        results.fold(Self::default(), |mut combined, result| {
+            combined.dice.extend(result.dice);
+            combined.bonus = combined.bonus.saturating_add(result.bonus);
+            combined.total = combined.total.saturating_add(result.total);
+            combined
+        })
+    }
+}
+
+#[derive(InspectorOptions, Reflect, Clone, Debug, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[reflect(InspectorOptions)]
 pub enum DiceType {
     D2,
@@ -30,14 +86,28 @@ pub enum DiceType {
     Static { value: u8 },
 }
 
+impl DiceType {
+    pub fn faces(&self) -> Option<u8> {
+        match self {
+            DiceType::D2 => Some(2),
+            DiceType::D3 => Some(3),
+            DiceType::D4 => Some(4),
+            DiceType::D6 => Some(6),
+            DiceType::D8 => Some(8),
+            DiceType::D12 => Some(12),
+            DiceType::Static { value: _ } => None,
+        }
+    }
+}
+
 impl Default for DiceType {
     fn default() -> Self {
         Self::D12
     }
 }
 
-impl Roll for DiceType {
-    fn roll(&self, rng: &mut impl TurboRand) -> u8 {
+impl DiceType {
+    fn roll_value(&self, rng: &mut impl TurboRand) -> u8 {
         match self {
             DiceType::Static { value } => *value,
             DiceType::D2 => rng.u8(1..3),
@@ -50,6 +120,12 @@ impl Roll for DiceType {
     }
 }
 
+impl Roll for DiceType {
+    fn roll(&self, rng: &mut impl TurboRand) -> RollResult {
+        RollResult::new(*self, vec![(self.roll_value(rng), true)])
+    }
+}
+
 impl DisplayBundle for DiceType {
     fn display_bundle(&self, assets: &MainGameAssets, icon_size: f32, parent: &mut UiChildBuilder) {
         let (is_atlas, position) = match self {
@@ -76,12 +152,114 @@ impl DisplayBundle for DiceType {
     }
 }
 
-#[derive(InspectorOptions, Reflect, Default, PartialEq, Eq, Clone, Debug, Copy, Deserialize)]
+#[derive(
+    InspectorOptions, Reflect, Default, PartialEq, Eq, Clone, Debug, Copy, Deserialize, Serialize,
+)]
 #[reflect(InspectorOptions)]
 pub enum DicePoolType {
     #[default]
     Single,
     Advantage,
+    Disadvantage,
+    KeepHighest {
+        rolled: u8,
+        kept: u8,
+    },
+    KeepLowest {
+        rolled: u8,
+        kept: u8,
+    },
+    Exploding,
+    ReRoll,
+    PreRolled(u8),
+}
+
+/// The maximum number of extra dice an exploding dice can add.
+pub const MAX_EXPLOSIONS: usize = 3;
+
+/// The maximum number of dice a single pool can roll.
+pub const MAX_POOL_SIZE: u8 = 5;
+
+/// Roll `rolled` dice, and only keep the highest or lowest `kept` of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeepDice {
+    pub rolled: u8,
+    pub kept: u8,
+    pub highest: bool,
+}
+
+impl DicePoolType {
+    /// Describes the pool in terms of dice rolled and kept, if it is a plain or keep pool.
+    pub fn keep(&self) -> Option<KeepDice> {
+        let (rolled, kept, highest) = match *self {
+            DicePoolType::Single => (1, 1, true),
+            DicePoolType::Advantage => (2, 1, true),
+            DicePoolType::Disadvantage => (2, 1, false),
+            DicePoolType::KeepHighest { rolled, kept } => (rolled, kept, true),
+            DicePoolType::KeepLowest { rolled, kept } => (rolled, kept, false),
+            _ => return None,
+        };
+        // Larger pools are rejected by content validation, and would be too slow to
+        // compute exact odds for.
+        let rolled = rolled.clamp(1, MAX_POOL_SIZE);
+        Some(KeepDice {
+            rolled,
+            kept: kept.clamp(1, rolled),
+            highest,
+        })
+    }
+
+    pub fn from_keep(keep: KeepDice) -> Self {
+        match keep {
+            KeepDice { rolled: 1, .. } => DicePoolType::Single,
+            KeepDice {
+                rolled: 2,
+                kept: 1,
+                highest: true,
+            } => DicePoolType::Advantage,
+            KeepDice {
+                rolled: 2,
+                kept: 1,
+                highest: false,
+            } => DicePoolType::Disadvantage,
+            KeepDice {
+                rolled,
+                kept,
+                highest: true,
+            } => DicePoolType::KeepHighest { rolled, kept },
+            KeepDice {
+                rolled,
+                kept,
+                highest: false,
+            } => DicePoolType::KeepLowest { rolled, kept },
+        }
+    }
+
+    /// The number of dice this pool rolls.
+    pub fn size(&self) -> u8 {
+        self.keep().map(|k| k.rolled).unwrap_or(1)
+    }
+
+    /// Shifts a keep pool one step towards keeping the highest dice -
+    /// cancelling out a lowest die if there is one, or rolling an extra die otherwise.
+    fn shift(&self, towards_highest: bool) -> Self {
+        let Some(keep) = self.keep() else {
+            return *self;
+        };
+        let keep = if keep.rolled == keep.kept || keep.highest == towards_highest {
+            KeepDice {
+                rolled: (keep.rolled + 1).min(MAX_POOL_SIZE),
+                kept: keep.kept,
+                highest: towards_highest,
+            }
+        } else {
+            KeepDice {
+                rolled: keep.rolled - 1,
+                ..keep
+            }
+        };
+        Self::from_keep(keep)
+    }
 }
 
 #[derive(InspectorOptions, Reflect, Component, Clone, Debug, Copy, Default, Deserialize)]
@@ -128,25 +306,200 @@ impl DicePool {
     }
 
     pub fn advantage(mut self) -> Self {
-        self.pool = DicePoolType::Advantage;
+        self.pool = self.pool.shift(true);
+        self
+    }
+
+    pub fn disadvantage(mut self) -> Self {
+        self.pool = self.pool.shift(false);
+        self
+    }
+
+    pub fn exploding(mut self) -> Self {
+        self.pool = DicePoolType::Exploding;
+        self
+    }
+
+    pub fn reroll(mut self) -> Self {
+        self.pool = DicePoolType::ReRoll;
+        self
+    }
+
+    pub fn pre_rolled(mut self, value: u8) -> Self {
+        self.pool = DicePoolType::PreRolled(value);
         self
     }
 }
 
-impl Roll for DicePool {
-    fn roll(&self, rng: &mut impl TurboRand) -> u8 {
-        match self.pool {
-            DicePoolType::Single => self.dice.roll(rng),
-            DicePoolType::Advantage => self.dice.roll(rng).max(self.dice.roll(rng)),
+impl DicePool {
+    /// Rolls the pool, keeping which pool each die came from.
+    pub fn roll_dice(&self, rng: &mut impl TurboRand) -> RolledPool {
+        RolledPool {
+            dice: self.dice,
+            pool: self.pool,
+            result: self.roll(rng),
         }
     }
 }
 
+impl Roll for DicePool {
+    fn roll(&self, rng: &mut impl TurboRand) -> RollResult {
+        let rolls = match self.pool {
+            DicePoolType::Exploding => {
+                let max = self.dice.faces().unwrap_or_default();
+                let mut rolls = vec![];
+                for _ in 0..=MAX_EXPLOSIONS {
+                    let roll = self.dice.roll_value(rng);
+                    rolls.push((roll, true));
+                    if roll != max {
+                        break;
+                    }
+                }
+                rolls
+            }
+            DicePoolType::ReRoll => match self.dice.roll_value(rng) {
+                1 => vec![(1, false), (self.dice.roll_value(rng), true)],
+                roll => vec![(roll, true)],
+            },
+            DicePoolType::PreRolled(value) => vec![(value, true)],
+            pool => {
+                let keep = pool.keep().unwrap_or(KeepDice {
+                    rolled: 1,
+                    kept: 1,
+                    highest: true,
+                });
+                let values = (0..keep.rolled)
+                    .map(|_| self.dice.roll_value(rng))
+                    .collect::<Vec<_>>();
+                let mut order = (0..values.len()).collect::<Vec<_>>();
+                order.sort_by_key(|index| values[*index]);
+                if keep.highest {
+                    order.reverse();
+                }
+                let kept = &order[..(keep.kept as usize).min(order.len())];
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| (*value, kept.contains(&index)))
+                    .collect()
+            }
+        };
+        RollResult::new(self.dice, rolls)
+    }
+}
+
+/// How a single pool rolled, kept so the roll can be looked back on.
+#[derive(
+    InspectorOptions, Reflect, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize,
+)]
+#[reflect(InspectorOptions)]
+pub struct RolledPool {
+    pub dice: DiceType,
+    pub pool: DicePoolType,
+    pub result: RollResult,
+}
+
+impl RolledPool {
+    /// Rolls each pool, totalling them the same way as rolling the slice.
+    pub fn roll_all(pools: &[&DicePool], rng: &mut impl TurboRand) -> (u8, Vec<Self>) {
+        let rolled = pools.iter().map(|p| p.roll_dice(rng)).collect::<Vec<_>>();
+        let total = if rolled.is_empty() {
+            1
+        } else {
+            RollResult::combine(rolled.iter().map(|r| r.result.clone())).total
+        };
+        (total, rolled)
+    }
+
+    /// A short description, like `2kh1 d6 [5, (2)] = 5`, with dropped dice in brackets.
+    pub fn describe(&self) -> String {
+        let dice = match (self.dice, self.dice.faces()) {
+            (DiceType::Static { .. }, _) => return format!("+{}", self.result.bonus),
+            (_, Some(faces)) => format!("d{faces}"),
+            (_, None) => "?".to_string(),
+        };
+        let pool = match self.pool {
+            DicePoolType::Single => String::new(),
+            DicePoolType::Exploding => "exploding ".to_string(),
+            DicePoolType::ReRoll => "re-roll ".to_string(),
+            DicePoolType::PreRolled(_) => "pre-rolled ".to_string(),
+            pool => pool
+                .keep()
+                .map(|k| {
+                    format!(
+                        "{}k{}{} ",
+                        k.rolled,
+                        if k.highest { "h" } else { "l" },
+                        k.kept
+                    )
+                })
+                .unwrap_or_default(),
+        };
+        let rolls = self
+            .result
+            .dice
+            .iter()
+            .map(|die| match die.kept {
+                true => die.value.to_string(),
+                false => format!("({})", die.value),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{pool}{dice} [{rolls}] = {}", self.result.total)
+    }
+}
+
 impl DisplayBundle for DicePool {
     fn display_bundle(&self, assets: &MainGameAssets, icon_size: f32, parent: &mut UiChildBuilder) {
-        if DicePoolType::Advantage == self.pool {
+        let modifier = match self.pool {
+            DicePoolType::Single
+            | DicePoolType::Disadvantage
+            | DicePoolType::KeepHighest { .. }
+            | DicePoolType::KeepLowest { .. } => None,
+            DicePoolType::Advantage => Some(Power::Advantage),
+            DicePoolType::Exploding => Some(Power::ExplodeDice),
+            DicePoolType::ReRoll => Some(Power::ReRoll),
+            DicePoolType::PreRolled(_) => Some(Power::PreRoll),
+        };
+        if let Some(modifier) = modifier {
+            node(dice_pool_modifier.nb(), parent, |p| {
+                modifier.display_bundle(assets, icon_size * 0.75, p);
+            });
+        } else if let Some(keep) = self
+            .pool
+            .keep()
+            .filter(|_| self.pool != DicePoolType::Single)
+        {
             node(dice_pool_modifier.nb(), parent, |p| {
-                Power::Advantage.display_bundle(assets, icon_size * 0.75, p);
+                p.spawn(TextBundle::from_section(
+                    format!(
+                        "{}k{}{}",
+                        keep.rolled,
+                        if keep.highest { "h" } else { "l" },
+                        keep.kept
+                    ),
+                    TextStyle {
+                        font: assets.druids_font.clone(),
+                        font_size: icon_size * 0.4,
+                        color: if keep.highest {
+                            colors::SUCCESS_COLOR
+                        } else {
+                            colors::CRITICAL_FAIL_COLOR
+                        },
+                    },
+                ));
+            });
+        }
+        if let DicePoolType::PreRolled(value) = self.pool {
+            node(pre_rolled_value.nb(), parent, |p| {
+                p.spawn(TextBundle::from_section(
+                    format!("{value}"),
+                    TextStyle {
+                        font: assets.druids_font.clone(),
+                        font_size: icon_size * 0.6,
+                        color: colors::CRITICAL_COLOR,
+                    },
+                ));
             });
         }
         self.dice.display_bundle(assets, icon_size, parent);
@@ -172,47 +525,144 @@ impl InitialPools {
     }
 }
 
-pub trait SimulateDice<const COUNT: usize> {
-    fn simulate(&self, rng: &mut impl TurboRand) -> Vec<(u8, f32)>;
+impl<T: Roll> Roll for &[&T] {
+    fn roll(&self, rng: &mut impl TurboRand) -> RollResult {
+        if self.is_empty() {
+            RollResult {
+                dice: vec![],
+                bonus: 1,
+                total: 1,
+            }
+        } else {
+            RollResult::combine(self.iter().map(|v| v.roll(rng)))
+        }
+    }
 }
 
-impl<T: Roll, const COUNT: usize> SimulateDice<COUNT> for T {
-    fn simulate(&self, rng: &mut impl TurboRand) -> Vec<(u8, f32)> {
-        let mut raw_results = Vec::with_capacity(COUNT);
-        for _ in 0..COUNT {
-            raw_results.push(self.roll(rng));
-        }
-        raw_results.sort();
-        let (max, counts_by_value) = raw_results.iter().fold(
-            (0usize, Vec::<(u8, usize)>::new()),
-            |(max, mut val), next| {
-                if let Some(last) = val.last_mut() {
-                    if last.0 == *next {
-                        let count = last.1 + 1;
-                        last.1 = count;
-                        return (max.max(count), val);
+#[cfg(test)]
+mod tests {
+    use bevy_turborand::{DelegatedRng, RngComponent};
+
+    use crate::in_game::encounter::dice_distribution::Distribution;
+
+    use super::*;
+
+    /// Monte Carlo sampling of a roll, as a cross-check for the exact
+    /// distributions in `dice_distribution`.
+    trait SimulateDice<const COUNT: usize> {
+        fn simulate(&self, rng: &mut impl TurboRand) -> Vec<(u8, f32)>;
+    }
+
+    impl<T: Roll, const COUNT: usize> SimulateDice<COUNT> for T {
+        fn simulate(&self, rng: &mut impl TurboRand) -> Vec<(u8, f32)> {
+            let mut raw_results = Vec::with_capacity(COUNT);
+            for _ in 0..COUNT {
+                raw_results.push(self.roll(rng).total);
+            }
+            raw_results.sort();
+            let (max, counts_by_value) = raw_results.iter().fold(
+                (0usize, Vec::<(u8, usize)>::new()),
+                |(max, mut val), next| {
+                    if let Some(last) = val.last_mut() {
+                        if last.0 == *next {
+                            let count = last.1 + 1;
+                            last.1 = count;
+                            return (max.max(count), val);
+                        }
                     }
-                }
-                val.push((*next, 1));
-                (max.max(1), val)
-            },
+                    val.push((*next, 1));
+                    (max.max(1), val)
+                },
+            );
+
+            let max = max as f32;
+
+            counts_by_value
+                .iter()
+                .map(|(val, count)| (*val, (*count as f32) / max))
+                .collect()
+        }
+    }
+
+    /// Samples enough rolls that every total should land within a few percent of the exact odds.
+    fn assert_matches_simulation<T: Roll + Distribution>(roll: &T) {
+        let mut rng = RngComponent::with_seed(7);
+        let simulated = <T as SimulateDice<200_000>>::simulate(roll, rng.get_mut());
+        let exact = roll.distribution().relative();
+        assert_eq!(
+            simulated.iter().map(|(v, _)| *v).collect::<Vec<_>>(),
+            exact.iter().map(|(v, _)| *v).collect::<Vec<_>>()
         );
+        for ((value, simulated), (_, exact)) in simulated.iter().zip(exact.iter()) {
+            assert!(
+                (simulated - exact).abs() < 0.05,
+                "{value}: simulated {simulated}, exact {exact}"
+            );
+        }
+    }
 
-        let max = max as f32;
+    #[test]
+    fn single_matches_simulation() {
+        assert_matches_simulation(&DicePool::d6());
+        assert_matches_simulation(&DicePool::d12());
+    }
 
-        counts_by_value
-            .iter()
-            .map(|(val, count)| (*val, (*count as f32) / max))
-            .collect()
+    #[test]
+    fn advantage_matches_simulation() {
+        assert_matches_simulation(&DicePool::d6().advantage());
+        assert_matches_simulation(&DicePool::d8().disadvantage());
+        assert_matches_simulation(&DicePool::d6().advantage().advantage());
     }
-}
 
-impl<T: Roll> Roll for &[&T] {
-    fn roll(&self, rng: &mut impl TurboRand) -> u8 {
-        if self.is_empty() {
-            1
-        } else {
-            self.iter().map(|v| v.roll(rng)).sum()
+    #[test]
+    fn convolved_pools_match_simulation() {
+        let (d6, d4) = (DicePool::d6(), DicePool::d4().advantage());
+        assert_matches_simulation(&[&d6, &d4].as_slice());
+    }
+
+    #[test]
+    fn static_bonus_matches_simulation() {
+        let (d8, bonus) = (DicePool::d8(), DicePool::bonus(3));
+        assert_matches_simulation(&[&d8, &bonus].as_slice());
+    }
+
+    #[test]
+    fn exploding_and_reroll_match_simulation() {
+        assert_matches_simulation(&DicePool::d4().exploding());
+        assert_matches_simulation(&DicePool::d6().reroll());
+    }
+
+    #[test]
+    fn advantage_keeps_the_highest_die() {
+        let mut rng = RngComponent::with_seed(3);
+        for _ in 0..100 {
+            let result = DicePool::d6().advantage().roll(rng.get_mut());
+            let kept = result.dice.iter().filter(|d| d.kept).collect::<Vec<_>>();
+            assert_eq!(result.dice.len(), 2);
+            assert_eq!(kept.len(), 1);
+            assert_eq!(
+                kept[0].value,
+                result.dice.iter().map(|d| d.value).max().unwrap()
+            );
+            assert_eq!(result.total, kept[0].value);
         }
     }
+
+    #[test]
+    fn oversized_keep_pools_are_clamped() {
+        let pool = DicePoolType::KeepHighest {
+            rolled: 50,
+            kept: 10,
+        };
+        let keep = pool.keep().unwrap();
+        assert_eq!((keep.rolled, keep.kept), (MAX_POOL_SIZE, MAX_POOL_SIZE));
+    }
+
+    #[test]
+    fn static_dice_are_a_bonus() {
+        let mut rng = RngComponent::with_seed(3);
+        let result = DicePool::bonus(2).roll(rng.get_mut());
+        assert!(result.dice.is_empty());
+        assert_eq!((result.bonus, result.total), (2, 2));
+    }
 }
@@ -1,9 +1,18 @@
 use bevy::prelude::*;
+use bevy_turborand::DelegatedRng;
 
-use crate::assets::MainGameAssets;
+use crate::{
+    assets::MainGameAssets,
+    in_game::{
+        mission::mission_types::MissionStage,
+        run_rng::{checkpoint, RngStream, RunRng},
+        story::PhaseRound,
+    },
+};
 
 use super::*;
 
+#[allow(clippy::too_many_arguments)]
 pub fn generate_encounter(
     mut commands: Commands,
     assets: Res<MainGameAssets>,
@@ -11,6 +20,9 @@ pub fn generate_encounter(
     challengers: Res<Assets<Challengers>>,
     players: Res<Assets<Players>>,
     initial_details: Option<Res<encounter_setup_types::EncounterInitialDetails>>,
+    mut run_rng: ResMut<RunRng>,
+    phase_round: Res<PhaseRound>,
+    mission_stage: Option<Res<MissionStage>>,
 ) {
     let (Some(locations), Some(challengers), Some(players)) = (
         locations.get(&assets.locations),
@@ -19,7 +31,15 @@ pub fn generate_encounter(
     ) else {
         return;
     };
-    let initial_details = initial_details.map(|v| v.clone()).unwrap_or_default();
+    let rng = run_rng
+        .stream(
+            RngStream::Encounters,
+            &checkpoint(&phase_round, mission_stage.as_deref()),
+        )
+        .get_mut();
+    let initial_details = initial_details
+        .map(|v| v.with_generated_challengers(challengers, locations, rng))
+        .unwrap_or_default();
     commands.remove_resource::<encounter_setup_types::EncounterInitialDetails>();
     commands.insert_resource(encounter_setup_types::EncounterSetup {
         title: initial_details.title,
@@ -28,10 +48,7 @@ pub fn generate_encounter(
             .location
             .and_then(|v| locations.get(&v).cloned()),
         player: players
-            .get(match initial_details.player_faction {
-                Faction::Knights => "player_knight",
-                Faction::Druids => "player_druid",
-            })
+            .get(initial_details.player_faction.player_key())
             .cloned(),
         challengers: initial_details
             .challengers
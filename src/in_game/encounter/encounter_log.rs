@@ -0,0 +1,343 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use bevy_ui_dsl::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    in_game::InGameUpdate,
+    storage,
+    ui::{classes::*, intermediary_node_bundles::IntoIntermediaryNodeBundle},
+};
+
+use super::{
+    actions::{ActionResult, Resolution},
+    dice_pools::RolledPool,
+    encounter_setup_types::EncounterSetup,
+    powers::Power,
+    sequencing::EncounterState,
+};
+
+const EXPORT_FILE: &str = "the-just-two-encounter-log";
+
+/// How many of the latest entries are kept in a saved run, so saves don't grow with every roll.
+const MAX_SAVED_ENTRIES: usize = 200;
+
+/// How far a line of mouse wheel scrolling moves the log.
+const SCROLL_LINE: f32 = 20.;
+const SCROLL_PAGE: f32 = 200.;
+
+pub struct EncounterLogPlugin;
+
+impl Plugin for EncounterLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EncounterLog>()
+            .register_type::<LogEntry>()
+            .add_systems(OnEnter(EncounterState::Introduction), start_encounter)
+            .add_systems(OnEnter(EncounterState::ActionChoice), display_log)
+            .add_systems(OnExit(EncounterState::ActionChoice), hide_log)
+            .add_systems(
+                InGameUpdate,
+                scroll_log.run_if(in_state(EncounterState::ActionChoice)),
+            );
+    }
+}
+
+/// Every roll made over the run, kept so the player can look back on how things went.
+#[derive(Resource, Reflect, InspectorOptions, Default, Clone, Serialize, Deserialize)]
+#[reflect(Resource, InspectorOptions)]
+pub struct EncounterLog {
+    /// The title of each encounter, in the order they were started.
+    pub encounters: Vec<String>,
+    pub entries: Vec<LogEntry>,
+}
+
+#[derive(Reflect, InspectorOptions, Clone, Debug, Serialize, Deserialize)]
+#[reflect(InspectorOptions)]
+pub struct LogEntry {
+    /// Index into [`EncounterLog::encounters`].
+    pub encounter: usize,
+    pub actor: String,
+    pub title: String,
+    pub pools: Vec<RolledPool>,
+    pub powers: Vec<Power>,
+    pub roll: u8,
+    pub result: ActionResult,
+    pub gap: u8,
+    /// Damage dealt by the action, for actions that can deal damage.
+    pub damage: Option<usize>,
+}
+
+impl LogEntry {
+    pub fn headline(&self) -> String {
+        format!("{}: {} - {:?}", self.actor, self.title, self.result)
+    }
+
+    pub fn details(&self) -> String {
+        let pools = self
+            .pools
+            .iter()
+            .map(|p| p.describe())
+            .collect::<Vec<_>>()
+            .join(" + ");
+        let mut details = format!("Rolled {} ({pools}), by {}", self.roll, self.gap);
+        if !self.powers.is_empty() {
+            let powers = self
+                .powers
+                .iter()
+                .map(|p| format!("{p:?}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            details.push_str(&format!(", using {powers}"));
+        }
+        if let Some(damage) = self.damage {
+            details.push_str(&format!(", dealt {damage}"));
+        }
+        details
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl LogFormat {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogFormat::Text => "Export Log (Text)",
+            LogFormat::Json => "Export Log (JSON)",
+        }
+    }
+}
+
+impl EncounterLog {
+    pub fn record(&mut self, title: &str, resolution: &Resolution, damage: Option<usize>) {
+        self.entries.push(LogEntry {
+            encounter: self.encounters.len().saturating_sub(1),
+            actor: resolution.actor.clone(),
+            title: title.to_string(),
+            pools: resolution.pools.clone(),
+            powers: resolution.powers.clone(),
+            roll: resolution.roll,
+            result: resolution.result,
+            gap: resolution.gap,
+            damage,
+        });
+    }
+
+    fn encounter_title(&self, encounter: usize) -> &str {
+        self.encounters
+            .get(encounter)
+            .map(|t| t.as_str())
+            .unwrap_or("Encounter")
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let mut encounter = None;
+        for entry in self.entries.iter() {
+            if encounter != Some(entry.encounter) {
+                encounter = Some(entry.encounter);
+                text.push_str(&format!(
+                    "== {} ==\n",
+                    self.encounter_title(entry.encounter)
+                ));
+            }
+            text.push_str(&format!("{}\n    {}\n", entry.headline(), entry.details()));
+        }
+        text
+    }
+
+    /// Writes the log out for the player, returning the file name if it was written.
+    pub fn export(&self, format: LogFormat) -> Option<String> {
+        let (file, data) = match format {
+            LogFormat::Text => (format!("{EXPORT_FILE}.txt"), self.to_text()),
+            LogFormat::Json => match serde_json::to_string_pretty(self) {
+                Ok(data) => (format!("{EXPORT_FILE}.json"), data),
+                Err(e) => {
+                    error!("Couldn't export encounter log: {e}");
+                    return None;
+                }
+            },
+        };
+        storage::export(&file, &data).then_some(file)
+    }
+
+    /// The log as it's kept in a saved run, with only the latest entries and the
+    /// encounters they came from.
+    pub fn for_save(&self) -> Self {
+        let skipped = self.entries.len().saturating_sub(MAX_SAVED_ENTRIES);
+        let first_encounter = self
+            .entries
+            .get(skipped)
+            .map_or(0, |entry| entry.encounter)
+            .min(self.encounters.len());
+        Self {
+            encounters: self.encounters[first_encounter..].to_vec(),
+            entries: self.entries[skipped..]
+                .iter()
+                .map(|entry| LogEntry {
+                    encounter: entry.encounter.saturating_sub(first_encounter),
+                    ..entry.clone()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Swaps an export button's label for where the log was written.
+pub fn show_exported(
+    file: &str,
+    button: Entity,
+    children: &Query<&Children>,
+    texts: &mut Query<&mut Text>,
+) {
+    for child in children.get(button).iter().flat_map(|c| c.iter()) {
+        let Ok(mut text) = texts.get_mut(*child) else {
+            continue;
+        };
+        if let Some(section) = text.sections.get_mut(0) {
+            section.value = format!("Exported {file}");
+        }
+    }
+}
+
+#[derive(Component)]
+struct Screen;
+
+/// The scrolling list inside the log panel, and how far it has been scrolled.
+#[derive(Component)]
+struct LogList(f32);
+
+fn start_encounter(mut log: ResMut<EncounterLog>, setup: Res<EncounterSetup>) {
+    log.encounters.push(
+        setup
+            .title
+            .clone()
+            .unwrap_or_else(|| "Encounter".to_string()),
+    );
+}
+
+fn display_log(mut commands: Commands, asset_server: Res<AssetServer>, log: Res<EncounterLog>) {
+    if log.entries.is_empty() {
+        return;
+    }
+    let mut list = None;
+    let r = root(c_encounter_log, &asset_server, &mut commands, |p| {
+        node(encounter_log_list.nb(), p, |p| {
+            // Newest first, so the last round is in view without scrolling.
+            let mut encounter = None;
+            for entry in log.entries.iter().rev() {
+                if encounter != Some(entry.encounter) {
+                    encounter = Some(entry.encounter);
+                    text(
+                        log.encounter_title(entry.encounter),
+                        (),
+                        (standard_text, druid_text),
+                        p,
+                    );
+                }
+                node(encounter_log_entry.nb(), p, |p| {
+                    text(entry.headline(), (), standard_text, p);
+                    text(entry.details(), (), log_detail_text, p);
+                });
+            }
+        })
+        .set(&mut list);
+    });
+    commands.entity(r).insert(Screen);
+    if let Some(list) = list {
+        commands.entity(list).insert(LogList(0.));
+    }
+}
+
+fn hide_log(mut commands: Commands, query: Query<Entity, With<Screen>>) {
+    for item in query.iter() {
+        commands.entity(item).despawn_recursive();
+    }
+}
+
+fn scroll_log(
+    mut wheel: EventReader<MouseWheel>,
+    keys: Res<Input<KeyCode>>,
+    mut lists: Query<(&mut LogList, &mut Style, &Node, &Parent)>,
+    panels: Query<&Node, Without<LogList>>,
+) {
+    let mut delta = wheel
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum::<f32>();
+    if keys.just_pressed(KeyCode::PageUp) {
+        delta += SCROLL_PAGE;
+    }
+    if keys.just_pressed(KeyCode::PageDown) {
+        delta -= SCROLL_PAGE;
+    }
+    if delta == 0. {
+        return;
+    }
+    for (mut list, mut style, node, parent) in lists.iter_mut() {
+        let visible = panels
+            .get(parent.get())
+            .map(|panel| panel.size().y)
+            .unwrap_or_default();
+        let max = (node.size().y - visible).max(0.);
+        list.0 = (list.0 + delta).clamp(-max, 0.);
+        style.top = Val::Px(list.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(encounter: usize) -> LogEntry {
+        LogEntry {
+            encounter,
+            actor: "Knight".to_string(),
+            title: "Strike".to_string(),
+            pools: vec![],
+            powers: vec![],
+            roll: 4,
+            result: ActionResult::Success,
+            gap: 0,
+            damage: None,
+        }
+    }
+
+    #[test]
+    fn saved_logs_keep_only_the_latest_entries() {
+        let log = EncounterLog {
+            encounters: (0..3).map(|i| format!("Encounter {i}")).collect(),
+            entries: (0..MAX_SAVED_ENTRIES + 10)
+                .map(|i| entry(if i < 10 { 0 } else { 1 + i % 2 }))
+                .collect(),
+        };
+        let saved = log.for_save();
+        assert_eq!(saved.entries.len(), MAX_SAVED_ENTRIES);
+        assert_eq!(saved.encounters, ["Encounter 1", "Encounter 2"]);
+        assert!(saved.entries.iter().all(|e| e.encounter < 2));
+        assert_eq!(
+            saved.encounter_title(saved.entries[0].encounter),
+            "Encounter 1"
+        );
+    }
+
+    #[test]
+    fn short_logs_are_saved_whole() {
+        let log = EncounterLog {
+            encounters: vec!["Encounter 0".to_string()],
+            entries: vec![entry(0), entry(0)],
+        };
+        let saved = log.for_save();
+        assert_eq!(saved.encounters, log.encounters);
+        assert_eq!(saved.entries.len(), 2);
+    }
+}
@@ -1,12 +1,14 @@
 use bevy::{prelude::*, reflect::TypeUuid, utils::HashMap};
 use bevy_common_assets::yaml::YamlAssetPlugin;
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
-use serde::Deserialize;
+use bevy_turborand::TurboRand;
+use serde::{Deserialize, Serialize};
 
 use crate::in_game::factions::Faction;
 
 use super::{
     challenger::ChallengerReference, location::LocationReference, player::PlayerReference,
+    Challengers, Locations,
 };
 
 pub struct EncounterSetupPlugin;
@@ -15,18 +17,102 @@ impl Plugin for EncounterSetupPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<EncounterSetup>()
             .register_type::<EncounterInitialDetails>()
+            .register_type::<ChallengerPool>()
             .add_plugins(YamlAssetPlugin::<Encounters>::new(&["en.yaml"]));
     }
 }
 
-#[derive(Resource, Reflect, InspectorOptions, Clone, Deserialize)]
+#[derive(Resource, Reflect, InspectorOptions, Clone, Deserialize, Serialize)]
 #[reflect(Resource, InspectorOptions)]
 pub struct EncounterInitialDetails {
     pub title: Option<String>,
     pub introduction: Option<String>,
     pub player_faction: Faction,
+    #[serde(default)]
     pub challengers: Vec<(usize, String)>,
     pub location: Option<String>,
+    /// Fills whatever challenger slots are left with generated challengers.
+    #[serde(default)]
+    pub generated: Option<ChallengerPool>,
+}
+
+impl EncounterInitialDetails {
+    /// Picks challengers from the pool until the location is full or the budget
+    /// runs out, adding them to the authored ones.
+    pub fn with_generated_challengers(
+        &self,
+        challengers: &Challengers,
+        locations: &Locations,
+        rng: &mut impl TurboRand,
+    ) -> Self {
+        let mut details = self.clone();
+        let Some(pool) = details.generated.take() else {
+            return details;
+        };
+        let Some(location) = self.location.as_ref().and_then(|l| locations.get(l)) else {
+            return details;
+        };
+        let authored = self
+            .challengers
+            .iter()
+            .map(|(count, _)| count)
+            .sum::<usize>();
+        let mut slots = location.challenger_slots.saturating_sub(authored);
+        let mut budget = pool.budget;
+        let candidates = pool.candidates(challengers, location);
+        while slots > 0 {
+            let affordable = candidates
+                .iter()
+                .filter(|(_, challenger)| challenger.threat <= budget)
+                .collect::<Vec<_>>();
+            let Some((key, challenger)) = rng.sample(&affordable) else {
+                break;
+            };
+            budget -= challenger.threat;
+            slots -= 1;
+            match details.challengers.last_mut() {
+                Some((count, last)) if last == *key => *count += 1,
+                _ => details.challengers.push((1, key.to_string())),
+            }
+        }
+        details
+    }
+}
+
+/// How an encounter's challengers are generated.
+#[derive(Reflect, InspectorOptions, Clone, Debug, Default, Deserialize, Serialize)]
+#[reflect(InspectorOptions)]
+pub struct ChallengerPool {
+    /// The total threat of the generated challengers. Mission nodes can override it.
+    #[serde(default)]
+    pub budget: usize,
+    /// Generated challengers need every one of these tags.
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl ChallengerPool {
+    /// The challengers that fit the pool's tags and the location, sorted by key.
+    pub fn candidates<'a>(
+        &self,
+        challengers: &'a Challengers,
+        location: &LocationReference,
+    ) -> Vec<(&'a String, &'a ChallengerReference)> {
+        let mut candidates = challengers
+            .iter()
+            .filter(|(_, challenger)| {
+                challenger.threat > 0
+                    && self.tags.iter().all(|tag| challenger.tags.contains(tag))
+                    && (location.tags.is_empty()
+                        || location
+                            .tags
+                            .iter()
+                            .any(|tag| challenger.tags.contains(tag)))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(key, _)| *key);
+        candidates
+    }
 }
 
 impl Default for EncounterInitialDetails {
@@ -37,6 +123,7 @@ impl Default for EncounterInitialDetails {
             challengers: vec![(1, "monster".to_string())],
             location: Some("grass".to_string()),
             introduction: Some("Let me introduce myself".to_string()),
+            generated: None,
         }
     }
 }
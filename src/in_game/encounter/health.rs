@@ -2,12 +2,14 @@ use bevy::prelude::*;
 use bevy_inspector_egui::InspectorOptions;
 use bevy_vector_shapes::{
     prelude::ShapePainter,
-    shapes::{Cap, LinePainter},
+    shapes::{Cap, DiscPainter, LinePainter},
 };
 use serde::Deserialize;
 
 use crate::{in_game::game_state::GameState, ui::colors};
 
+use super::{actions::RevealAfterRoll, status_effects::StatusEffects};
+
 pub struct HealthPlugin;
 
 impl Plugin for HealthPlugin {
@@ -17,6 +19,10 @@ impl Plugin for HealthPlugin {
             .add_systems(
                 Update,
                 (draw_health_bars).run_if(in_state(GameState::Encounter)),
+            )
+            .add_systems(
+                PostUpdate,
+                update_shown_health.run_if(in_state(GameState::Encounter)),
             );
     }
 }
@@ -27,13 +33,45 @@ pub struct CurrentHealth(pub usize);
 #[derive(Component, Reflect, InspectorOptions, Default, Clone, Copy, Debug, Deserialize)]
 pub struct MaxHealth(pub usize);
 
+/// The health drawn on a creature's bar. It only catches up with [`CurrentHealth`]
+/// once the roll that changed it has been revealed, so the bar doesn't give the result away.
+#[derive(Component, Default)]
+struct ShownHealth(usize);
+
 const HEALTH_BAR_END: Vec3 = Vec3::new(50., 0., 0.);
 const HEALTH_BAR_OFFSET: Vec3 = Vec3::new(-25., 0., 0.);
 const HEALTH_BAR_WIDTH: f32 = 15.;
+const STATUS_ICON_OFFSET: Vec3 = Vec3::new(0., 18., 0.);
+const STATUS_ICON_SPACING: Vec3 = Vec3::new(14., 0., 0.);
+const STATUS_ICON_SIZE: f32 = 5.;
+
+/// Runs after the frame's resolutions have been displayed, so any roll behind a change
+/// in health is already waiting to be revealed.
+fn update_shown_health(
+    mut commands: Commands,
+    mut creatures: Query<(Entity, &CurrentHealth, Option<&mut ShownHealth>)>,
+    revealing: Query<(), With<RevealAfterRoll>>,
+) {
+    let revealing = !revealing.is_empty();
+    for (entity, health, shown) in creatures.iter_mut() {
+        match shown {
+            Some(mut shown) if !revealing && shown.0 != health.0 => shown.0 = health.0,
+            Some(_) => {}
+            None => {
+                commands.entity(entity).insert(ShownHealth(health.0));
+            }
+        }
+    }
+}
 
 fn draw_health_bars(
     mut painter: ShapePainter,
-    creatures: Query<(&GlobalTransform, &CurrentHealth, &MaxHealth)>,
+    creatures: Query<(
+        &GlobalTransform,
+        &ShownHealth,
+        &MaxHealth,
+        Option<&StatusEffects>,
+    )>,
     camera: Query<(&GlobalTransform, &Camera), With<Camera3d>>,
     camera_2d: Query<(&GlobalTransform, &Camera), With<Camera2d>>,
 ) {
@@ -44,7 +82,7 @@ fn draw_health_bars(
         return;
     };
 
-    for (transform, health, max) in creatures.iter() {
+    for (transform, health, max, status) in creatures.iter() {
         let Some(position) = camera.world_to_ndc(camera_transform, transform.translation()) else {
             continue;
         };
@@ -71,5 +109,15 @@ fn draw_health_bars(
         painter.color = health_color;
         painter.cap = Cap::Round;
         painter.line(Vec3::ZERO, HEALTH_BAR_END * health_ratio);
+
+        for (i, effect) in status.iter().flat_map(|s| s.0.iter()).enumerate() {
+            painter.set_translation(
+                position + HEALTH_BAR_OFFSET + STATUS_ICON_OFFSET + STATUS_ICON_SPACING * i as f32,
+            );
+            painter.color = colors::OVERLAY_COLOR;
+            painter.circle(STATUS_ICON_SIZE + 2.);
+            painter.color = effect.effect.color();
+            painter.circle(STATUS_ICON_SIZE);
+        }
     }
 }
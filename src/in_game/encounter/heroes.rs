@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use serde::{Deserialize, Serialize};
+
+use crate::in_game::{factions::Faction, game_state::GameState};
+
+use super::{
+    actions::{ActionDefinition, Escaped, Fled},
+    challenger::Challenger,
+    encounter_resolution::ChallengerCompleted,
+    encounter_setup_types::EncounterSetup,
+    health::{CurrentHealth, MaxHealth},
+    player::{Player, PlayerReference},
+    sequencing::EncounterState,
+};
+
+pub struct HeroPlugin;
+
+impl Plugin for HeroPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Heroes>()
+            .register_type::<HeroRecord>()
+            .register_type::<LevelUp>()
+            .add_systems(
+                Update,
+                award_experience
+                    .run_if(in_state(GameState::Encounter).and_then(resource_exists::<Heroes>())),
+            )
+            .add_systems(
+                OnEnter(EncounterState::EncounterResolved),
+                record_hero.run_if(resource_exists::<Heroes>()),
+            )
+            .add_systems(
+                OnEnter(GameState::WorldMap),
+                finish_mission.run_if(resource_exists::<Heroes>()),
+            );
+    }
+}
+
+/// What a hero gains on reaching `xp`. Levels are reached in order.
+#[derive(Reflect, InspectorOptions, Deserialize, Default, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub struct LevelUp {
+    pub xp: usize,
+    #[serde(default)]
+    pub max_health: usize,
+    #[serde(default)]
+    pub combat_actions: Vec<ActionDefinition>,
+}
+
+/// A hero's progress over the run. Health carries from one encounter to the
+/// next, and is only restored by levelling up or mission rewards.
+#[derive(Reflect, InspectorOptions, Serialize, Deserialize, Default, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub struct HeroRecord {
+    pub current_health: Option<usize>,
+    pub xp: usize,
+    pub level: usize,
+    /// Maximum health gained from mission rewards.
+    #[serde(default)]
+    pub bonus_max_health: usize,
+    /// Encounters seen through in the current mission, used to share out its rewards.
+    #[serde(default)]
+    pub mission_encounters: usize,
+}
+
+impl HeroRecord {
+    fn unlocked<'a>(&self, hero: &'a PlayerReference) -> impl Iterator<Item = &'a LevelUp> {
+        hero.levels.iter().take(self.level)
+    }
+
+    pub fn max_health(&self, hero: &PlayerReference) -> MaxHealth {
+        MaxHealth(
+            hero.health.0
+                + self.bonus_max_health
+                + self.unlocked(hero).map(|l| l.max_health).sum::<usize>(),
+        )
+    }
+
+    pub fn current_health(&self, hero: &PlayerReference) -> usize {
+        let max = self.max_health(hero).0;
+        self.current_health.unwrap_or(max).min(max)
+    }
+
+    pub fn combat_actions(&self, hero: &PlayerReference) -> Vec<ActionDefinition> {
+        hero.combat_actions
+            .iter()
+            .chain(self.unlocked(hero).flat_map(|l| l.combat_actions.iter()))
+            .cloned()
+            .collect()
+    }
+
+    /// The XP needed for the next level, if there is one.
+    pub fn next_level(&self, hero: &PlayerReference) -> Option<usize> {
+        hero.levels.get(self.level).map(|l| l.xp)
+    }
+
+    /// Levels up as far as the hero's XP allows, returning the number of levels gained.
+    pub fn level_up(&mut self, hero: &PlayerReference) -> usize {
+        let mut gained = 0;
+        while let Some(next) = hero.levels.get(self.level) {
+            if self.xp < next.xp {
+                break;
+            }
+            if let Some(health) = self.current_health.as_mut() {
+                *health += next.max_health;
+            }
+            self.level += 1;
+            gained += 1;
+        }
+        gained
+    }
+}
+
+#[derive(Resource, Reflect, InspectorOptions, Serialize, Deserialize, Default, Clone, Debug)]
+#[reflect(Resource, InspectorOptions)]
+pub struct Heroes {
+    pub knights: HeroRecord,
+    pub druids: HeroRecord,
+}
+
+impl Heroes {
+    pub fn get(&self, faction: Faction) -> &HeroRecord {
+        match faction {
+            Faction::Knights => &self.knights,
+            Faction::Druids => &self.druids,
+        }
+    }
+
+    pub fn get_mut(&mut self, faction: Faction) -> &mut HeroRecord {
+        match faction {
+            Faction::Knights => &mut self.knights,
+            Faction::Druids => &mut self.druids,
+        }
+    }
+
+    pub fn finish_mission(&mut self) {
+        for record in [&mut self.knights, &mut self.druids] {
+            record.mission_encounters = 0;
+        }
+    }
+
+    /// Who receives each of `count` mission rewards - the hero who saw more of the
+    /// mission through gets them all, and an even split alternates between the two.
+    pub fn reward_recipients(&self, count: usize) -> Vec<Faction> {
+        let (knights, druids) = (
+            self.knights.mission_encounters,
+            self.druids.mission_encounters,
+        );
+        (0..count)
+            .map(|i| match knights.cmp(&druids) {
+                std::cmp::Ordering::Greater => Faction::Knights,
+                std::cmp::Ordering::Less => Faction::Druids,
+                std::cmp::Ordering::Equal if i % 2 == 0 => Faction::Knights,
+                std::cmp::Ordering::Equal => Faction::Druids,
+            })
+            .collect()
+    }
+}
+
+type DefeatedChallengers<'w, 's, 'a> =
+    Query<'w, 's, &'a Challenger, (Added<ChallengerCompleted>, Without<Escaped>)>;
+
+fn award_experience(
+    defeated: DefeatedChallengers,
+    setup: Option<Res<EncounterSetup>>,
+    mut heroes: ResMut<Heroes>,
+) {
+    let Some(setup) = setup else {
+        return;
+    };
+    for challenger in defeated.iter() {
+        heroes.get_mut(setup.player_faction).xp += challenger.xp;
+    }
+}
+
+fn record_hero(
+    player: Query<&CurrentHealth, With<Player>>,
+    setup: Res<EncounterSetup>,
+    fled: Option<Res<Fled>>,
+    mut heroes: ResMut<Heroes>,
+) {
+    let (Some(hero), Ok(health)) = (&setup.player, player.get_single()) else {
+        return;
+    };
+    let record = heroes.get_mut(setup.player_faction);
+    record.current_health = Some(health.0);
+    if fled.is_none() {
+        record.mission_encounters += 1;
+    }
+    let gained = record.level_up(hero);
+    if gained > 0 {
+        info!("{} reached level {}", hero.name, record.level + 1);
+    }
+}
+
+fn finish_mission(mut heroes: ResMut<Heroes>) {
+    heroes.finish_mission();
+}
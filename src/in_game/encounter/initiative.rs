@@ -0,0 +1,128 @@
+use std::cmp::Reverse;
+
+use bevy::{ecs::query::Has, prelude::*};
+use bevy_inspector_egui::InspectorOptions;
+use bevy_ui_dsl::*;
+use serde::Deserialize;
+
+use crate::ui::{classes::*, colors, intermediary_node_bundles::IntoIntermediaryNodeBundle};
+
+use super::{
+    action_resolutions::ActiveResolution, challenger::Challenger,
+    encounter_resolution::ChallengerCompleted, player::Player, sequencing::EncounterState,
+    EncounterEntity,
+};
+
+pub struct InitiativePlugin;
+
+impl Plugin for InitiativePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Initiative>()
+            .add_systems(OnEnter(EncounterState::ActionChoice), draw_turn_order)
+            .add_systems(
+                Update,
+                highlight_active_turn.run_if(in_state(EncounterState::OutcomeResolution)),
+            )
+            .add_systems(
+                OnEnter(EncounterState::CheckEncounterResolution),
+                highlight_active_turn,
+            );
+    }
+}
+
+/// How quickly a combatant acts. Higher initiative resolves first.
+#[derive(
+    Component,
+    Reflect,
+    InspectorOptions,
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub struct Initiative(pub u8);
+
+/// Sorts combatants into the order they act - highest initiative first, with the
+/// player winning ties and challengers otherwise acting in placement order.
+pub fn turn_order(initiative: Initiative, challenger_id: Option<usize>) -> impl Ord {
+    (Reverse(initiative), challenger_id.is_some(), challenger_id)
+}
+
+#[derive(Component)]
+struct TurnOrderStrip;
+
+#[derive(Component)]
+struct TurnOrderEntry(Entity);
+
+type CombatantsQuery<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'a Initiative,
+        Option<&'a Player>,
+        Option<&'a Challenger>,
+    ),
+    Without<ChallengerCompleted>,
+>;
+
+fn draw_turn_order(
+    mut commands: Commands,
+    combatants: CombatantsQuery,
+    strip: Query<Entity, With<TurnOrderStrip>>,
+    asset_server: Res<AssetServer>,
+) {
+    for entity in strip.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let mut order = combatants
+        .iter()
+        .filter_map(|(entity, initiative, player, challenger)| {
+            let name = player
+                .map(|p| p.name.clone())
+                .or_else(|| challenger.map(|c| c.name.clone()))?;
+            Some((
+                turn_order(*initiative, challenger.map(|c| c.id)),
+                entity,
+                name,
+            ))
+        })
+        .collect::<Vec<_>>();
+    order.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut entries = vec![];
+    let r = root(turn_order_root, &asset_server, &mut commands, |p| {
+        for (_, entity, name) in order.iter() {
+            let entry = node(turn_order_entry.nb(), p, |p| {
+                text(name, (), standard_text, p);
+            });
+            entries.push((entry, *entity));
+        }
+    });
+    commands.entity(r).insert((TurnOrderStrip, EncounterEntity));
+    for (entry, entity) in entries {
+        commands.entity(entry).insert(TurnOrderEntry(entity));
+    }
+}
+
+fn highlight_active_turn(
+    active: Query<&Parent, With<ActiveResolution>>,
+    mut entries: Query<(&TurnOrderEntry, &mut BackgroundColor)>,
+    completed: Query<Has<ChallengerCompleted>>,
+) {
+    let acting = active.iter().next().map(|p| p.get());
+    for (entry, mut background) in entries.iter_mut() {
+        background.0 = if Some(entry.0) == acting {
+            colors::PRIMARY_COLOR
+        } else if completed.get(entry.0).unwrap_or(true) {
+            colors::CARD_COLOR_BLOCKED
+        } else {
+            colors::CARD_COLOR
+        };
+    }
+}
@@ -34,6 +34,9 @@ pub struct LocationReference {
     pub challenger_slots: usize,
     pub fog: Option<(Color, f32, f32)>,
     pub ambient: Option<(Color, f32)>,
+    /// Generated challengers need to share one of these, if there are any.
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Reflect, InspectorOptions)]
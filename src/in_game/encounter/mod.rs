@@ -2,16 +2,23 @@ mod action_choice;
 mod action_resolutions;
 pub mod actions;
 mod challenger;
+mod challenger_strategy;
+pub mod dice_distribution;
 pub mod dice_pools;
 mod encounter_assets;
+pub mod encounter_log;
 mod encounter_resolution;
 mod health;
+pub mod heroes;
+mod initiative;
 mod introduction;
 pub mod location;
 mod player;
 pub mod powers;
 mod probability_setup;
 pub mod sequencing;
+pub mod simulation;
+pub mod status_effects;
 
 pub mod encounter_generation;
 pub mod encounter_setup_types;
@@ -25,7 +32,11 @@ use bevy_asset_loader::prelude::DynamicAssets;
 use bevy_inspector_egui::quick::StateInspectorPlugin;
 
 use crate::{
-    in_game::encounter::{challenger::Challenger, health::CurrentHealth, player::Player},
+    in_game::encounter::{
+        challenger::Challenger,
+        health::{CurrentHealth, MaxHealth},
+        player::Player,
+    },
     materialized_scene::MaterializedSceneBundle,
     ui::colors::{DEFAULT_AMBIENT, DEFAULT_CLEAR},
 };
@@ -39,17 +50,26 @@ use self::{
         setup_encounter_assets, EncounterAssetPlugin, EncounterAssets, Materials, SceneBundler,
     },
     encounter_generation::generate_encounter,
+    encounter_log::EncounterLogPlugin,
     encounter_resolution::EncounterResolutionPlugin,
     encounter_setup_types::EncounterSetupPlugin,
     health::HealthPlugin,
+    heroes::{HeroPlugin, Heroes},
+    initiative::InitiativePlugin,
     introduction::IntroductionPlugin,
     location::LocationPlugin,
     player::PlayerPlugin,
     probability_setup::ProbabilitySetupPlugin,
     sequencing::EncounterState,
+    status_effects::{StatusEffectPlugin, StatusEffects},
 };
 
-use super::{factions::Faction, game_state::GameState, InGameUpdate};
+use super::{
+    difficulty::Difficulty,
+    endless::{effective_difficulty, EndlessRun},
+    game_state::GameState,
+    InGameUpdate,
+};
 
 pub use self::challenger::Challengers;
 pub use self::location::Locations;
@@ -78,7 +98,11 @@ impl Plugin for EncounterPlugin {
                 ActionPlugin,
                 EncounterResolutionPlugin,
                 EncounterSetupPlugin,
+                StatusEffectPlugin,
+                InitiativePlugin,
+                HeroPlugin,
             ))
+            .add_plugins(EncounterLogPlugin)
             .add_systems(
                 OnEnter(GameState::Encounter),
                 generate_encounter.run_if(not(resource_exists::<
@@ -110,6 +134,7 @@ fn start_encounter(
     commands.insert_resource(NextState(Some(EncounterState::Loading)));
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_encounter(
     mut commands: Commands,
     setup: Res<encounter_setup_types::EncounterSetup>,
@@ -118,8 +143,12 @@ fn spawn_encounter(
     gltf: Res<Assets<Gltf>>,
     gltf_node: Res<Assets<GltfNode>>,
     camera: Query<Entity, With<Camera3d>>,
+    heroes: Option<Res<Heroes>>,
+    difficulty: Res<Difficulty>,
+    endless: Option<Res<EndlessRun>>,
 ) {
     let bundler = SceneBundler::new(&assets, &materials, &gltf, &gltf_node);
+    let difficulty = effective_difficulty(&difficulty, endless.as_deref());
     if let (Some(location), Some(player)) = (&setup.location, &setup.player) {
         info!("Spawning Location {location:?}");
         if let Some(bundle) = bundler.scene(&location.scene) {
@@ -173,15 +202,21 @@ fn spawn_encounter(
                 },
                 ..bundle.clone()
             };
+            let record = heroes
+                .as_ref()
+                .map(|heroes| heroes.get(setup.player_faction).clone())
+                .unwrap_or_default();
             commands.spawn((
                 Player {
                     name: player.name.clone(),
-                    combat_actions: player.combat_actions.clone(),
+                    combat_actions: record.combat_actions(player),
                 },
                 bundle,
                 EncounterEntity,
-                CurrentHealth(player.health.0),
-                player.health,
+                CurrentHealth(record.current_health(player)),
+                record.max_health(player),
+                player.initiative,
+                StatusEffects::default(),
                 Name::new("Player"),
             ));
         }
@@ -219,13 +254,19 @@ fn spawn_encounter(
                             name: challenger.name.clone(),
                             available_actions: challenger.available_actions.clone(),
                             published_actions: challenger.published_actions.clone(),
+                            imposes_disadvantage: challenger.imposes_disadvantage,
+                            strategy: challenger.strategy,
+                            xp: challenger.xp,
                         },
                         bundle,
                         EncounterEntity,
+                        challenger.initiative,
+                        StatusEffects::default(),
                         Name::new(format!("{} - {challenger_id}", challenger.name)),
                     ));
                     if let Some(health) = challenger.health {
-                        entity.insert((health, CurrentHealth(health.0)));
+                        let health = difficulty.health(health.0);
+                        entity.insert((MaxHealth(health), CurrentHealth(health)));
                     }
                     challenger_id += 1;
                 }
@@ -10,11 +10,14 @@ use serde::Deserialize;
 use crate::materialized_scene::MaterializedSceneReference;
 
 use super::{
-    actions::{ActionChoice, ActionDefinition, ActionTarget, ActionType, PlayerActionBundle},
+    actions::{ActionChoice, ActionDefinition, ActionTarget, PlayerActionBundle},
     challenger::Challenger,
     encounter_resolution::ChallengerCompleted,
     health::{CurrentHealth, MaxHealth},
+    heroes::LevelUp,
+    initiative::Initiative,
     sequencing::{EncounterState, PublishAvailableActions},
+    status_effects::AppliesEffects,
 };
 
 pub struct PlayerPlugin;
@@ -38,6 +41,11 @@ pub struct PlayerReference {
     pub scene: MaterializedSceneReference,
     pub combat_actions: Vec<ActionDefinition>,
     pub health: MaxHealth,
+    #[serde(default)]
+    pub initiative: Initiative,
+    /// Levels the hero can reach over a run, in order.
+    #[serde(default)]
+    pub levels: Vec<LevelUp>,
 }
 
 #[derive(Reflect, InspectorOptions, Component)]
@@ -54,6 +62,10 @@ impl Players {
     pub fn get(&self, key: &str) -> Option<&PlayerReference> {
         self.0.get(key)
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &PlayerReference)> {
+        self.0.iter()
+    }
 }
 
 fn publish_combat_actions(
@@ -61,26 +73,50 @@ fn publish_combat_actions(
     players: Query<(Entity, &Player)>,
     challengers: Query<(Entity, &Challenger, &CurrentHealth), Without<ChallengerCompleted>>,
 ) {
+    let mut remaining = challengers
+        .iter()
+        .map(|(entity, challenger, _)| (entity, challenger))
+        .collect::<Vec<_>>();
+    remaining.sort_by_key(|(_, challenger)| challenger.id);
+    let entities = remaining.iter().map(|(e, _)| *e).collect::<Vec<_>>();
+
     for (entity, player) in players.iter() {
         commands.entity(entity).with_children(|p| {
-            for (ch_entity, challenger, _health) in challengers.iter() {
-                for action in player.combat_actions.iter() {
+            for (index, (_, challenger)) in remaining.iter().enumerate() {
+                for action in player.combat_actions.iter().filter(|action| {
+                    action.action_type.targets_challenger() && !action.targeting.offered_once()
+                }) {
                     let action_choice = ActionChoice {
                         title: action.choice.title.replace("**", &challenger.name),
                         ..action.choice.clone()
                     };
                     p.spawn(PlayerActionBundle {
                         action_choice,
-                        action_type: match action.action_type {
-                            super::actions::ActionType::Attack { base_damage } => {
-                                ActionType::Attack { base_damage }
-                            }
-                            _ => action.action_type.clone(),
-                        },
-                        target: ActionTarget(Some(ch_entity)),
+                        action_type: action.action_type.clone(),
+                        target: ActionTarget(action.targeting.hits(&entities, index)),
+                        targeting: action.targeting,
+                        effects: AppliesEffects(action.effects.clone()),
+                        outcomes: action.outcomes.named(&challenger.name),
                     });
                 }
             }
+            for action in player.combat_actions.iter().filter(|action| {
+                !action.action_type.targets_challenger() || action.targeting.offered_once()
+            }) {
+                let target = if action.action_type.targets_challenger() {
+                    entities.clone()
+                } else {
+                    vec![]
+                };
+                p.spawn(PlayerActionBundle {
+                    action_choice: action.choice.clone(),
+                    action_type: action.action_type.clone(),
+                    target: ActionTarget(target),
+                    targeting: action.targeting,
+                    effects: AppliesEffects(action.effects.clone()),
+                    outcomes: action.outcomes.clone(),
+                });
+            }
         });
     }
 }
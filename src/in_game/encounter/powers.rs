@@ -1,26 +1,30 @@
 use bevy::{prelude::*, reflect::Reflect};
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use bevy_turborand::TurboRand;
 use bevy_ui_dsl::{node, UiChildBuilder};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     assets::MainGameAssets,
     ui::{colors, spawn_icon, DisplayBundle},
 };
 
-use super::dice_pools::{DicePool, DicePoolType, DiceType};
+use super::dice_pools::{DicePool, DicePoolType, DiceType, Roll, MAX_POOL_SIZE};
 
-#[derive(Component, InspectorOptions, Reflect, Clone, Copy)]
+#[derive(
+    Component, InspectorOptions, Reflect, Clone, Copy, Debug, PartialEq, Serialize, Deserialize,
+)]
 #[reflect(InspectorOptions)]
 pub enum Power {
     SplitDice,
-    // CombineDice,
+    CombineDice,
     AddDice(DiceType),
     Advantage,
     StaticBonus(u8),
-    // DefaceDice(DiceType),
-    // ExplodeDice,
-    // PreRoll,
-    // ReRoll,
+    DefaceDice(DiceType),
+    ExplodeDice,
+    PreRoll,
+    ReRoll,
 }
 
 #[derive(InspectorOptions, Reflect, Default)]
@@ -29,6 +33,7 @@ pub enum PowerTargetingType {
     #[default]
     Single,
     Action,
+    AllPools,
 }
 
 impl Power {
@@ -36,6 +41,7 @@ impl Power {
         match self {
             Power::AddDice(_) => PowerTargetingType::Action,
             Power::StaticBonus(_) => PowerTargetingType::Action,
+            Power::CombineDice => PowerTargetingType::AllPools,
             _ => PowerTargetingType::Single,
         }
     }
@@ -50,23 +56,41 @@ impl Power {
                         false
                     }
             }
+            Power::CombineDice => find_combinable_pair(input).is_some(),
             Power::AddDice(_) => input.is_empty(),
             Power::Advantage => {
                 input.len() == 1
                     && if let Some(DicePool { dice, pool }) = input.first() {
-                        if *pool == DicePoolType::Advantage {
-                            return false;
-                        }
-                        !matches!(dice, DiceType::Static { value: _ })
+                        dice.faces().is_some()
+                            && pool.keep().is_some()
+                            && pool.size() < MAX_POOL_SIZE
                     } else {
-                        true
+                        false
+                    }
+            }
+            Power::ExplodeDice | Power::PreRoll | Power::ReRoll => {
+                input.len() == 1
+                    && if let Some(DicePool { dice, pool }) = input.first() {
+                        *pool == DicePoolType::Single && dice.faces().is_some()
+                    } else {
+                        false
                     }
             }
             Power::StaticBonus(_) => input.is_empty(),
+            Power::DefaceDice(target) => {
+                input.len() == 1
+                    && if let (Some(first), Some(target)) = (input.first(), target.faces()) {
+                        // A pre-rolled die keeps its value, which a smaller die couldn't roll.
+                        !matches!(first.pool, DicePoolType::PreRolled(_))
+                            && first.dice.faces().map(|f| f > target).unwrap_or(false)
+                    } else {
+                        false
+                    }
+            }
         }
     }
 
-    pub fn apply(&self, input: &[&DicePool]) -> Vec<DicePool> {
+    pub fn apply(&self, input: &[&DicePool], rng: &mut impl TurboRand) -> Vec<DicePool> {
         match self {
             Power::SplitDice => input
                 .iter()
@@ -85,13 +109,60 @@ impl Power {
                         .collect::<Vec<_>>()
                 })
                 .collect(),
+            Power::CombineDice => {
+                let Some((first, second, dice)) = find_combinable_pair(input) else {
+                    return input.iter().map(|v| **v).collect();
+                };
+                input
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != second)
+                    .map(|(i, v)| if i == first { DicePool::new(dice) } else { **v })
+                    .collect()
+            }
             Power::AddDice(d) => vec![DicePool::new(*d)],
             Power::Advantage => input.iter().map(|v| (**v).advantage()).collect(),
             Power::StaticBonus(v) => vec![DicePool::bonus(*v)],
+            Power::DefaceDice(d) => input
+                .iter()
+                .map(|v| DicePool {
+                    dice: *d,
+                    pool: v.pool,
+                })
+                .collect(),
+            Power::ExplodeDice => input.iter().map(|v| (**v).exploding()).collect(),
+            Power::PreRoll => input
+                .iter()
+                .map(|v| (**v).pre_rolled(v.roll(rng).total))
+                .collect(),
+            Power::ReRoll => input.iter().map(|v| (**v).reroll()).collect(),
         }
     }
 }
 
+/// Finds two plain dice of the same type that can merge into a larger one,
+/// returning their positions and the merged dice.
+fn find_combinable_pair(input: &[&DicePool]) -> Option<(usize, usize, DiceType)> {
+    input.iter().enumerate().find_map(|(i, first)| {
+        let combined = match first.dice {
+            DiceType::D2 => DiceType::D4,
+            DiceType::D3 => DiceType::D6,
+            DiceType::D4 => DiceType::D8,
+            DiceType::D6 => DiceType::D12,
+            _ => return None,
+        };
+        if first.pool != DicePoolType::Single {
+            return None;
+        }
+        input
+            .iter()
+            .enumerate()
+            .skip(i + 1)
+            .find(|(_, second)| second.dice == first.dice && second.pool == DicePoolType::Single)
+            .map(|(j, _)| (i, j, combined))
+    })
+}
+
 impl Default for Power {
     fn default() -> Self {
         Self::StaticBonus(1)
@@ -130,27 +201,60 @@ impl DisplayBundle for Power {
                                 dice.display_bundle(assets, icon_size, p);
                             });
                     }
+                    Power::CombineDice => {
+                        power_label("><", assets, parent);
+                    }
                     Power::Advantage => {
                         parent.spawn(spawn_icon(9, assets, icon_size));
                     }
+                    Power::DefaceDice(dice) => {
+                        parent
+                            .spawn(NodeBundle {
+                                ..Default::default()
+                            })
+                            .with_children(|p| {
+                                p.spawn(TextBundle::from_section(
+                                    "-".to_string(),
+                                    TextStyle {
+                                        font: assets.knights_font.clone(),
+                                        font_size: 40.,
+                                        color: colors::PRIMARY_BUTTON_TEXT,
+                                    },
+                                ));
+                                dice.display_bundle(assets, icon_size, p);
+                            });
+                    }
+                    Power::ExplodeDice => {
+                        power_label("!!", assets, parent);
+                    }
+                    Power::PreRoll => {
+                        power_label("?", assets, parent);
+                    }
+                    Power::ReRoll => {
+                        power_label("1>", assets, parent);
+                    }
                     Power::StaticBonus(v) => {
-                        parent.spawn(
-                            TextBundle::from_section(
-                                format!("+{v}"),
-                                TextStyle {
-                                    font: assets.druids_font.clone(),
-                                    font_size: 30.,
-                                    color: colors::PRIMARY_BUTTON_TEXT,
-                                },
-                            )
-                            .with_style(Style {
-                                padding: UiRect::all(Val::Px(5.)),
-                                ..default()
-                            }),
-                        );
+                        power_label(format!("+{v}"), assets, parent);
                     }
                 };
             },
         );
     }
 }
+
+fn power_label(label: impl Into<String>, assets: &MainGameAssets, parent: &mut UiChildBuilder) {
+    parent.spawn(
+        TextBundle::from_section(
+            label,
+            TextStyle {
+                font: assets.druids_font.clone(),
+                font_size: 30.,
+                color: colors::PRIMARY_BUTTON_TEXT,
+            },
+        )
+        .with_style(Style {
+            padding: UiRect::all(Val::Px(5.)),
+            ..default()
+        }),
+    );
+}
@@ -1,10 +1,12 @@
 use bevy::{ecs::query::Has, prelude::*};
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
-use bevy_turborand::{DelegatedRng, GlobalRng};
+use bevy_turborand::DelegatedRng;
 
 use super::{
     actions::{ActionChoice, ChallengerAction, Resolution},
+    challenger::Challenger,
     dice_pools::*,
+    player::Player,
     powers::{Power, PowerTargetingType},
     sequencing::EncounterState,
 };
@@ -13,7 +15,12 @@ use bevy_ui_dsl::*;
 
 use crate::{
     assets::MainGameAssets,
-    in_game::InGameUpdate,
+    in_game::{
+        mission::mission_types::MissionStage,
+        run_rng::{checkpoint, RngStream, RunRng},
+        story::PhaseRound,
+        InGameUpdate,
+    },
     ui::{
         buttons::{focus_button, focus_text_button, focused_button_activated},
         classes::*,
@@ -26,8 +33,6 @@ mod dice_pools;
 
 mod handle_powers;
 
-mod iter_averager;
-
 pub struct ProbabilitySetupPlugin;
 
 impl Plugin for ProbabilitySetupPlugin {
@@ -56,7 +61,8 @@ impl Plugin for ProbabilitySetupPlugin {
                     handle_powers::update_powers.before(handle_powers::clear_updated_powers),
                     dice_pools::clear_updated_dice_pool,
                     handle_powers::clear_updated_powers,
-                    dice_pools::update_probability_distibution,
+                    dice_pools::update_probability_distibution
+                        .before(dice_pools::clear_updated_dice_pool),
                     handle_powers::update_current_focusables,
                     handle_powers::update_resolve_button,
                     focused_button_activated.pipe(handle_powers::process_input),
@@ -73,7 +79,7 @@ struct Screen;
 struct DicePoolControl(Entity);
 
 #[derive(Component)]
-struct ProbabilityVisualizer(Entity, Vec<(u8, f32)>);
+struct ProbabilityVisualizer(Entity);
 
 #[derive(Component)]
 pub struct PowerContainer;
@@ -104,6 +110,18 @@ struct UpdatedDicePool;
 #[derive(Component)]
 pub struct UpdatePowers;
 
+/// The powers spent on an action during setup, so they can be logged with its roll.
+#[derive(Component, Default, Clone)]
+pub struct AppliedPowers(Vec<Power>);
+
+impl AppliedPowers {
+    pub fn with(&self, power: Power) -> Self {
+        let mut powers = self.0.clone();
+        powers.push(power);
+        Self(powers)
+    }
+}
+
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
@@ -190,9 +208,7 @@ fn setup(
         commands.entity(*ctl).insert(DicePoolControl(*target));
     }
     for (ctl, target) in probability_visualizers.iter() {
-        commands
-            .entity(*ctl)
-            .insert(ProbabilityVisualizer(*target, vec![]));
+        commands.entity(*ctl).insert(ProbabilityVisualizer(*target));
     }
     for (ctl, target) in action_buttons.iter() {
         commands.entity(*ctl).insert(Buttons::Action(*target));
@@ -206,22 +222,56 @@ fn exit(mut commands: Commands, query: Query<Entity, With<Screen>>) {
     }
 }
 
+type ResolvingActionsQuery<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'a ActionChoice,
+        &'a Children,
+        Option<&'a Parent>,
+        Option<&'a AppliedPowers>,
+    ),
+>;
+
 fn resolve_actions(
     mut commands: Commands,
     dice_pools: Query<&DicePool>,
-    updated_actions: Query<(Entity, &ActionChoice, &Children)>,
-    mut global_rng: ResMut<GlobalRng>,
+    updated_actions: ResolvingActionsQuery,
+    actors: Query<(Option<&Player>, Option<&Challenger>)>,
+    mut run_rng: ResMut<RunRng>,
+    phase_round: Res<PhaseRound>,
+    mission_stage: Option<Res<MissionStage>>,
 ) {
-    for (entity, action, dice_pool_entities) in updated_actions.iter() {
+    let rng = run_rng
+        .stream(
+            RngStream::Dice,
+            &checkpoint(&phase_round, mission_stage.as_deref()),
+        )
+        .get_mut();
+    for (entity, action, dice_pool_entities, actor, powers) in updated_actions.iter() {
         let dice_pools = dice_pool_entities
             .iter()
             .flat_map(|e| dice_pools.get(*e).ok())
             .collect::<Vec<_>>();
-        let roll = dice_pools.as_slice().roll(global_rng.get_mut());
+        let (roll, pools) = RolledPool::roll_all(&dice_pools, rng);
         let (result, gap) = action.evaluate(roll);
+        let actor = match actor.and_then(|p| actors.get(p.get()).ok()) {
+            Some((Some(player), _)) => player.name.clone(),
+            Some((_, Some(challenger))) => challenger.name.clone(),
+            _ => "Unknown".to_string(),
+        };
         commands
             .entity(entity)
             .despawn_descendants()
-            .insert(Resolution { roll, result, gap });
+            .remove::<AppliedPowers>()
+            .insert(Resolution {
+                roll,
+                result,
+                gap,
+                actor,
+                pools,
+                powers: powers.map(|p| p.0.clone()).unwrap_or_default(),
+            });
     }
 }
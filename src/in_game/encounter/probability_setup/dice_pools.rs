@@ -1,8 +1,11 @@
 use crate::{
     assets::MainGameAssets,
     in_game::encounter::{
-        actions::{ActionChoice, ActionResult},
+        actions::{ActionChoice, ActionResult, ActionTarget, ChallengerAction},
+        challenger::Challenger,
+        dice_distribution::Distribution,
         dice_pools::DicePool,
+        status_effects::StatusEffects,
     },
     ui::{buttons::*, colors, DisplayBundle},
 };
@@ -11,14 +14,55 @@ use bevy_ui_dsl::root;
 
 use super::*;
 
-pub(crate) fn setup_initial_pools(mut commands: Commands, query: Query<(Entity, &ActionChoice)>) {
-    for (entity, choice) in query.iter() {
+type InitialActionsQuery<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'a ActionChoice,
+        &'a ActionTarget,
+        Option<&'a Parent>,
+        Has<ChallengerAction>,
+    ),
+>;
+
+pub(crate) fn setup_initial_pools(
+    mut commands: Commands,
+    query: InitialActionsQuery,
+    challengers: Query<&Challenger>,
+    status_effects: Query<&StatusEffects>,
+) {
+    let weakened = |entity: Option<Entity>| {
+        entity
+            .and_then(|entity| status_effects.get(entity).ok())
+            .map(|status| status.is_weakened())
+            .unwrap_or(false)
+    };
+    for (entity, choice, target, actor, is_challenger) in query.iter() {
+        let actor = actor.map(|p| p.get());
+        // The player rolls for every action, so a weakened challenger is easier to defend against.
+        let favour = if is_challenger {
+            i8::from(weakened(actor)) - i8::from(weakened(target.primary()))
+        } else {
+            let imposes_disadvantage = challengers
+                .iter_many(target.0.iter())
+                .any(|challenger| challenger.imposes_disadvantage);
+            -i8::from(weakened(actor)) - i8::from(imposes_disadvantage)
+        };
         commands
             .entity(entity)
             .insert(UpdatedDicePool)
             .with_children(|p| {
                 for pool in choice.dice_pool.iter() {
-                    p.spawn(*pool);
+                    if pool.dice.faces().is_none() {
+                        p.spawn(*pool);
+                    } else if favour < 0 {
+                        p.spawn(pool.disadvantage());
+                    } else if favour > 0 {
+                        p.spawn(pool.advantage());
+                    } else {
+                        p.spawn(*pool);
+                    }
                 }
             });
     }
@@ -77,15 +121,11 @@ pub(super) fn clear_updated_dice_pool(
 pub(super) fn update_probability_distibution(
     mut commands: Commands,
     dice_pools: Query<&DicePool>,
-    updated_actions: Query<(&Children, &ActionChoice, Has<UpdatedDicePool>)>,
+    updated_actions: Query<(&Children, &ActionChoice), With<UpdatedDicePool>>,
     dice_pool_display: Query<(Entity, &ProbabilityVisualizer)>,
-    mut global_rng: ResMut<GlobalRng>,
 ) {
-    for (display_entity, ProbabilityVisualizer(action_entity, stored_simulation)) in
-        dice_pool_display.iter()
-    {
-        let Ok((dice_pool_entities, action, just_updated)) = updated_actions.get(*action_entity)
-        else {
+    for (display_entity, ProbabilityVisualizer(action_entity)) in dice_pool_display.iter() {
+        let Ok((dice_pool_entities, action)) = updated_actions.get(*action_entity) else {
             continue;
         };
         let dice_pools = dice_pool_entities
@@ -93,24 +133,13 @@ pub(super) fn update_probability_distibution(
             .flat_map(|e| dice_pools.get(*e).ok())
             .collect::<Vec<_>>();
 
-        let simulation =
-            SimulateDice::<100>::simulate(&dice_pools.as_slice(), global_rng.get_mut());
-
-        let simulation = if just_updated {
-            simulation
-        } else {
-            iter_averager::Averager::<'_, _, _, 1, 20>(
-                simulation.iter().peekable(),
-                stored_simulation.iter().peekable(),
-            )
-            .collect::<Vec<(u8, f32)>>()
-        };
+        let distribution = dice_pools.as_slice().distribution().relative();
 
         commands
             .entity(display_entity)
             .despawn_descendants()
             .with_children(|p| {
-                for (value, rate) in simulation.iter() {
+                for (value, rate) in distribution.iter() {
                     let (result_type, _) = action.evaluate(*value);
                     let result_type = match result_type {
                         ActionResult::CriticalFail => colors::CRITICAL_FAIL_COLOR,
@@ -142,7 +171,6 @@ pub(super) fn update_probability_distibution(
                         });
                     });
                 }
-            })
-            .insert(ProbabilityVisualizer(*action_entity, simulation));
+            });
     }
 }
@@ -1,5 +1,14 @@
 use crate::ui::buttons::TypedFocusedButtonQuery;
 use bevy::prelude::*;
+use bevy_turborand::DelegatedRng;
+
+use crate::in_game::{
+    encounter::encounter_setup_types::EncounterSetup,
+    factions::Faction,
+    mission::mission_types::MissionStage,
+    run_rng::{checkpoint, RngStream, RunRng},
+    story::PhaseRound,
+};
 use bevy_ui_navigation::prelude::{FocusState, Focusable};
 
 use super::*;
@@ -28,6 +37,7 @@ pub(crate) fn update_resolve_button(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn process_input(
     In(focused): In<Option<Entity>>,
     mut commands: Commands,
@@ -36,6 +46,11 @@ pub(crate) fn process_input(
     targeting: Res<TargetingTypes>,
     power_containers: Query<Entity, With<PowerContainer>>,
     dice_pools: Query<&DicePool>,
+    action_pools: Query<&Children, With<ActionChoice>>,
+    applied_powers: Query<&AppliedPowers>,
+    mut run_rng: ResMut<RunRng>,
+    phase_round: Res<PhaseRound>,
+    mission_stage: Option<Res<MissionStage>>,
 ) {
     let Some(focused) = focused else {
         return;
@@ -43,6 +58,13 @@ pub(crate) fn process_input(
     let Some((_, btn)) = interaction_query.get(focused).ok() else {
         return;
     };
+    let rng = run_rng
+        .stream(
+            RngStream::Powers,
+            &checkpoint(&phase_round, mission_stage.as_deref()),
+        )
+        .get_mut();
+    let applied = |action: &Entity| applied_powers.get(*action).cloned().unwrap_or_default();
     let power_targets =
         if let TargetingTypes::PowerTarget(targeting, power_entity, power) = targeting.as_ref() {
             Some((targeting, power_entity, power))
@@ -72,6 +94,9 @@ pub(crate) fn process_input(
                 let Ok(dice) = dice_pools.get(*pool) else {
                     return;
                 };
+                if !power.valid_targets(&[dice]) {
+                    return;
+                }
 
                 commands.insert_resource(TargetingTypes::SelectPower);
                 commands.entity(*power_entity).despawn();
@@ -79,9 +104,9 @@ pub(crate) fn process_input(
 
                 commands
                     .entity(*action)
-                    .insert(UpdatedDicePool)
+                    .insert((UpdatedDicePool, applied(action).with(*power)))
                     .with_children(|p| {
-                        for dice in power.apply(&[dice]).iter() {
+                        for dice in power.apply(&[dice], rng).iter() {
                             p.spawn(*dice);
                         }
                     });
@@ -90,15 +115,18 @@ pub(crate) fn process_input(
                 }
             }
         }
-        Buttons::Action(action) => {
-            if let Some((PowerTargetingType::Action, power_entity, power)) = power_targets {
+        Buttons::Action(action) => match power_targets {
+            Some((PowerTargetingType::Action, power_entity, power)) => {
+                if !power.valid_targets(&[]) {
+                    return;
+                }
                 commands.insert_resource(TargetingTypes::SelectPower);
                 commands.entity(*power_entity).despawn();
                 commands
                     .entity(*action)
-                    .insert(UpdatedDicePool)
+                    .insert((UpdatedDicePool, applied(action).with(*power)))
                     .with_children(|p| {
-                        for dice in power.apply(&[]).iter() {
+                        for dice in power.apply(&[], rng).iter() {
                             p.spawn(*dice);
                         }
                     });
@@ -106,7 +134,38 @@ pub(crate) fn process_input(
                     commands.entity(entity).insert(UpdatePowers);
                 }
             }
-        }
+            Some((PowerTargetingType::AllPools, power_entity, power)) => {
+                let Ok(children) = action_pools.get(*action) else {
+                    return;
+                };
+                let (pool_entities, pools): (Vec<_>, Vec<_>) = children
+                    .iter()
+                    .filter_map(|e| dice_pools.get(*e).ok().map(|pool| (*e, pool)))
+                    .unzip();
+                if !power.valid_targets(&pools) {
+                    return;
+                }
+
+                commands.insert_resource(TargetingTypes::SelectPower);
+                commands.entity(*power_entity).despawn();
+                for pool in pool_entities.iter() {
+                    commands.entity(*pool).despawn();
+                }
+
+                commands
+                    .entity(*action)
+                    .insert((UpdatedDicePool, applied(action).with(*power)))
+                    .with_children(|p| {
+                        for dice in power.apply(&pools, rng).iter() {
+                            p.spawn(*dice);
+                        }
+                    });
+                for entity in power_containers.iter() {
+                    commands.entity(entity).insert(UpdatePowers);
+                }
+            }
+            _ => {}
+        },
     }
 }
 
@@ -133,7 +192,7 @@ pub(crate) fn update_current_focusables(
             }
         }
         TargetingTypes::PowerTarget(targeting_type, _, _) => match targeting_type {
-            PowerTargetingType::Action => {
+            PowerTargetingType::Action | PowerTargetingType::AllPools => {
                 for (button, mut focusable) in buttons.iter_mut() {
                     let focus = matches!(button, Buttons::Resolve | Buttons::Action(_));
                     let is_focusable = focusable.state() != FocusState::Blocked;
@@ -169,7 +228,8 @@ pub(crate) fn update_current_focusables(
 pub(super) fn update_powers(
     mut commands: Commands,
     power_containers: Query<Entity, With<UpdatePowers>>,
-    powers: Query<(Entity, &Power)>,
+    powers: Query<(Entity, &Power, &Faction)>,
+    setup: Res<EncounterSetup>,
     assets: Res<MainGameAssets>,
     asset_server: Res<AssetServer>,
 ) {
@@ -177,7 +237,11 @@ pub(super) fn update_powers(
     for container in power_containers.iter() {
         info!("Updating Powers");
         let root = root(powers_container.nb(), &asset_server, &mut commands, |p| {
-            for (entity, power) in powers.iter() {
+            // Only the active hero's powers are on offer.
+            for (entity, power, _) in powers
+                .iter()
+                .filter(|(_, _, faction)| **faction == setup.player_faction)
+            {
                 power_buttons.push((
                     focus_button(power_card_container.nb(), apply_power_card_state, p, |p| {
                         power.display_bundle(&assets, 50., p);
@@ -0,0 +1,403 @@
+use bevy_turborand::TurboRand;
+
+use super::{
+    actions::{ActionChoice, ActionDefinition, ActionResult, ActionType, Consequence, Targeting},
+    challenger::ChallengerReference,
+    challenger_strategy::{ChallengerDecision, DecisionContext},
+    dice_distribution::Distribution,
+    dice_pools::{DicePool, Roll},
+    encounter_setup_types::EncounterInitialDetails,
+    initiative::turn_order,
+    player::PlayerReference,
+    Challengers, Locations, Players,
+};
+
+/// Encounters that haven't been decided after this many turns count as a loss.
+pub const MAX_TURNS: usize = 100;
+
+/// How a simulated player picks an action each turn.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PlayerPolicy {
+    /// Any available action against any remaining challenger.
+    Random,
+    /// The action with the highest expected damage.
+    #[default]
+    Greedy,
+    /// The highest expected damage against the challenger with the least health.
+    FocusWeakest,
+}
+
+impl PlayerPolicy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "random" => Some(Self::Random),
+            "greedy" => Some(Self::Greedy),
+            "focus-weakest" | "focus_weakest" => Some(Self::FocusWeakest),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EncounterOutcome {
+    pub won: bool,
+    pub fled: bool,
+    pub turns: usize,
+    pub damage_taken: usize,
+}
+
+/// An encounter resolved against its content, mirroring `generate_encounter`
+/// and the challenger placement in `spawn_encounter`. Powers, status effects
+/// and follow-up actions aren't simulated.
+///
+/// Challengers without health are only completed through story choices, which
+/// a policy can't weigh, so they're left out and counted in `skipped`.
+pub struct SimulatedEncounter {
+    player: PlayerReference,
+    challengers: Vec<ChallengerReference>,
+    pub skipped: usize,
+}
+
+struct SimulatedChallenger<'a> {
+    reference: &'a ChallengerReference,
+    health: Option<(usize, usize)>,
+    defending: usize,
+    completed: bool,
+}
+
+impl<'a> SimulatedChallenger<'a> {
+    fn defeated(&self) -> bool {
+        self.completed || matches!(self.health, Some((0, _)))
+    }
+}
+
+enum Actor {
+    /// The player, with every challenger their action hits.
+    Player(Vec<usize>),
+    Challenger(usize),
+}
+
+impl SimulatedEncounter {
+    pub fn new(
+        details: &EncounterInitialDetails,
+        challengers: &Challengers,
+        players: &Players,
+        locations: &Locations,
+    ) -> Option<Self> {
+        let player = players.get(details.player_faction.player_key())?.clone();
+        let slots = details
+            .location
+            .as_ref()
+            .and_then(|location| locations.get(location))
+            .map(|location| location.challenger_slots)?;
+        let (challengers, skipped): (Vec<_>, Vec<_>) = details
+            .challengers
+            .iter()
+            .filter_map(|(count, key)| challengers.get(key).map(|c| (*count, c)))
+            .flat_map(|(count, challenger)| std::iter::repeat(challenger.clone()).take(count))
+            .take(slots)
+            .partition(|challenger| challenger.health.is_some());
+        Some(Self {
+            player,
+            challengers,
+            skipped: skipped.len(),
+        })
+    }
+
+    /// Whether any challengers are left to fight once the ones without health are skipped.
+    pub fn has_challengers(&self) -> bool {
+        !self.challengers.is_empty()
+    }
+
+    pub fn play(&self, policy: PlayerPolicy, rng: &mut impl TurboRand) -> EncounterOutcome {
+        let max_health = self.player.health.0;
+        let mut health = max_health;
+        let mut damage_taken = 0;
+        let mut challengers = self
+            .challengers
+            .iter()
+            .map(|reference| SimulatedChallenger {
+                reference,
+                health: reference.health.map(|h| (h.0, h.0)),
+                defending: 0,
+                completed: false,
+            })
+            .collect::<Vec<_>>();
+
+        for turn in 1..=MAX_TURNS {
+            let mut actions = challengers
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| !c.defeated())
+                .filter_map(|(i, c)| {
+                    let reference = c.reference;
+                    let context = DecisionContext {
+                        own_health: c.health,
+                        player_health: Some((health, max_health)),
+                    };
+                    reference
+                        .strategy
+                        .choose(&reference.available_actions, &context, rng)
+                        .map(|action| (Actor::Challenger(i), action))
+                })
+                .collect::<Vec<_>>();
+            if let Some((targets, action)) = self.choose_action(policy, &challengers, rng) {
+                actions.push((Actor::Player(targets), action));
+            }
+            // The same order as `trigger_next_resolution`.
+            actions.sort_by_key(|(actor, action)| {
+                let order = match actor {
+                    Actor::Player(_) => turn_order(self.player.initiative, None),
+                    Actor::Challenger(i) => {
+                        turn_order(challengers[*i].reference.initiative, Some(*i))
+                    }
+                };
+                (action.action_type.resolution_priority(), order)
+            });
+
+            let mut defending = 0;
+            for challenger in challengers.iter_mut() {
+                challenger.defending = 0;
+            }
+
+            for (actor, action) in actions {
+                let down = match actor {
+                    Actor::Player(_) => health == 0,
+                    Actor::Challenger(i) => challengers[i].defeated(),
+                };
+                if down {
+                    continue;
+                }
+                let is_challenger = matches!(actor, Actor::Challenger(_));
+                let pools = match &actor {
+                    Actor::Player(targets) => player_pools(&action.choice, &challengers, targets),
+                    Actor::Challenger(_) => action.choice.dice_pool.iter().copied().collect(),
+                };
+                let result = roll(&action.choice, &pools, rng);
+                if let ActionType::Text = action.action_type {
+                    let challenger = match &actor {
+                        Actor::Player(targets) => targets.first().copied(),
+                        Actor::Challenger(i) => Some(*i),
+                    };
+                    let consequences = action.outcomes.get(result).into_iter();
+                    for consequence in consequences.flat_map(|o| o.consequences.iter()) {
+                        match consequence {
+                            Consequence::PlayerHealth(change) => {
+                                let before = health;
+                                health = health
+                                    .saturating_add_signed(*change as isize)
+                                    .min(max_health);
+                                damage_taken += before.saturating_sub(health);
+                            }
+                            Consequence::ChallengerHealth(change) => {
+                                let Some((current, max)) =
+                                    challenger.and_then(|i| challengers[i].health.as_mut())
+                                else {
+                                    continue;
+                                };
+                                *current =
+                                    current.saturating_add_signed(*change as isize).min(*max);
+                            }
+                            Consequence::CompleteChallenger => {
+                                if let Some(i) = challenger {
+                                    challengers[i].completed = true;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    continue;
+                }
+                if !result.lands(is_challenger) {
+                    continue;
+                }
+                match (&action.action_type, actor) {
+                    (ActionType::Attack { .. }, Actor::Player(targets)) => {
+                        let damage = action.action_type.damage(result, false) as usize;
+                        for (index, target) in targets.iter().enumerate() {
+                            let challenger = &mut challengers[*target];
+                            let damage = action
+                                .targeting
+                                .share(damage, index, targets.len())
+                                .saturating_sub(challenger.defending);
+                            if let Some((current, _)) = challenger.health.as_mut() {
+                                *current = current.saturating_sub(damage);
+                            }
+                        }
+                    }
+                    (ActionType::Attack { .. }, Actor::Challenger(_)) => {
+                        let damage = (action.action_type.damage(result, true) as usize)
+                            .saturating_sub(defending)
+                            .min(health);
+                        health -= damage;
+                        damage_taken += damage;
+                    }
+                    (ActionType::Heal { amount }, Actor::Player(_)) => {
+                        health = (health + result.scale(*amount) as usize).min(max_health);
+                    }
+                    (ActionType::Heal { amount }, Actor::Challenger(i)) => {
+                        if let Some((current, max)) = challengers[i].health.as_mut() {
+                            *current = (*current + result.scale(*amount) as usize).min(*max);
+                        }
+                    }
+                    (ActionType::Defend { reduction }, Actor::Player(_)) => {
+                        defending = result.scale(*reduction) as usize;
+                    }
+                    (ActionType::Defend { reduction }, Actor::Challenger(i)) => {
+                        challengers[i].defending = result.scale(*reduction) as usize;
+                    }
+                    (ActionType::Flee, Actor::Player(_)) => {
+                        return EncounterOutcome {
+                            won: false,
+                            fled: true,
+                            turns: turn,
+                            damage_taken,
+                        };
+                    }
+                    (ActionType::Flee, Actor::Challenger(i)) => challengers[i].completed = true,
+                    _ => {}
+                }
+            }
+
+            if health == 0 {
+                return EncounterOutcome {
+                    won: false,
+                    fled: false,
+                    turns: turn,
+                    damage_taken,
+                };
+            }
+            if challengers.iter().all(|c| c.defeated()) {
+                return EncounterOutcome {
+                    won: true,
+                    fled: false,
+                    turns: turn,
+                    damage_taken,
+                };
+            }
+        }
+
+        EncounterOutcome {
+            won: false,
+            fled: false,
+            turns: MAX_TURNS,
+            damage_taken,
+        }
+    }
+
+    /// Every action the player could take this turn, as in `publish_combat_actions`
+    /// and the published actions of each challenger.
+    fn choose_action<'a>(
+        &'a self,
+        policy: PlayerPolicy,
+        challengers: &[SimulatedChallenger<'a>],
+        rng: &mut impl TurboRand,
+    ) -> Option<(Vec<usize>, &'a ActionDefinition)> {
+        let remaining = (0..challengers.len())
+            .filter(|i| !challengers[*i].defeated())
+            .collect::<Vec<_>>();
+        let actions = &self.player.combat_actions;
+        let options = remaining
+            .iter()
+            .enumerate()
+            .flat_map(|(index, i)| {
+                let remaining = &remaining;
+                actions
+                    .iter()
+                    .filter(|action| {
+                        action.action_type.targets_challenger() && !action.targeting.offered_once()
+                    })
+                    .map(move |action| (action.targeting.hits(remaining, index), action))
+                    .chain(
+                        challengers[*i]
+                            .reference
+                            .published_actions
+                            .iter()
+                            .map(move |action| (vec![*i], action)),
+                    )
+            })
+            .chain(actions.iter().filter_map(|action| {
+                if !action.action_type.targets_challenger() {
+                    Some((vec![], action))
+                } else if action.targeting.offered_once() {
+                    Some((remaining.clone(), action))
+                } else {
+                    None
+                }
+            }))
+            .collect::<Vec<_>>();
+
+        let expected = |(targets, action): &(Vec<usize>, &ActionDefinition)| {
+            let damage =
+                expected_damage(action, &player_pools(&action.choice, challengers, targets));
+            match action.targeting {
+                Targeting::Cleave {
+                    split_damage: true, ..
+                }
+                | Targeting::All { split_damage: true } => damage,
+                _ => damage * targets.len() as f64,
+            }
+        };
+
+        match policy {
+            PlayerPolicy::Random => rng.sample(&options).cloned(),
+            PlayerPolicy::Greedy => options
+                .iter()
+                .max_by(|a, b| expected(a).total_cmp(&expected(b)))
+                .cloned(),
+            PlayerPolicy::FocusWeakest => {
+                let weakest = *remaining.iter().min_by_key(|i| {
+                    challengers[**i]
+                        .health
+                        .map_or(usize::MAX, |(current, _)| current)
+                })?;
+                options
+                    .iter()
+                    .filter(|(targets, _)| targets.contains(&weakest))
+                    .max_by(|a, b| expected(a).total_cmp(&expected(b)))
+                    .cloned()
+            }
+        }
+    }
+}
+
+/// The pools a player rolls for an action, as set up in `setup_initial_pools`.
+fn player_pools(
+    choice: &ActionChoice,
+    challengers: &[SimulatedChallenger],
+    targets: &[usize],
+) -> Vec<DicePool> {
+    let imposes_disadvantage = targets
+        .iter()
+        .any(|i| challengers[*i].reference.imposes_disadvantage);
+    choice
+        .dice_pool
+        .iter()
+        .map(|pool| {
+            if imposes_disadvantage && pool.dice.faces().is_some() {
+                pool.disadvantage()
+            } else {
+                *pool
+            }
+        })
+        .collect()
+}
+
+fn expected_damage(action: &ActionDefinition, pools: &[DicePool]) -> f64 {
+    let pools = pools.iter().collect::<Vec<_>>();
+    pools
+        .as_slice()
+        .distribution()
+        .iter()
+        .map(|(value, probability)| {
+            let (result, _) = action.choice.evaluate(value);
+            action.action_type.damage(result, false) as f64 * probability
+        })
+        .sum()
+}
+
+fn roll(choice: &ActionChoice, pools: &[DicePool], rng: &mut impl TurboRand) -> ActionResult {
+    let pools = pools.iter().collect::<Vec<_>>();
+    let (result, _) = choice.evaluate(pools.as_slice().roll(rng).total);
+    result
+}
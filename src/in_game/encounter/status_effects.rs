@@ -0,0 +1,198 @@
+use bevy::{ecs::query::Has, prelude::*};
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use serde::Deserialize;
+
+use crate::{in_game::game_state::GameState, ui::colors};
+
+use super::{
+    action_resolutions::ActiveResolution,
+    actions::{ActionTarget, ChallengerAction, Resolution},
+    challenger::Challenger,
+    encounter_resolution::ChallengerCompleted,
+    health::CurrentHealth,
+    player::Player,
+    sequencing::EncounterState,
+};
+
+pub struct StatusEffectPlugin;
+
+impl Plugin for StatusEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<StatusEffectType>()
+            .register_type::<StatusEffect>()
+            .register_type::<StatusEffects>()
+            .add_systems(
+                Update,
+                apply_status_effects.run_if(in_state(EncounterState::OutcomeResolution)),
+            )
+            .add_systems(
+                OnEnter(EncounterState::CheckEncounterResolution),
+                tick_status_effects,
+            );
+    }
+}
+
+#[derive(InspectorOptions, Reflect, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(InspectorOptions)]
+pub enum StatusEffectType {
+    /// Deals damage at the end of every round.
+    Bleed { damage: u8 },
+    /// Absorbs incoming damage until it is used up.
+    Shield { amount: u8 },
+    /// Skips the challenger's next action.
+    Stun,
+    /// Rolls made by the affected combatant are made with disadvantage.
+    Weakened,
+}
+
+impl StatusEffectType {
+    pub fn color(&self) -> Color {
+        match self {
+            StatusEffectType::Bleed { .. } => colors::CRITICAL_FAIL_COLOR,
+            StatusEffectType::Shield { .. } => colors::PRIMARY_COLOR,
+            StatusEffectType::Stun => colors::CRITICAL_COLOR,
+            StatusEffectType::Weakened => colors::DRUIDS_SECONDARY,
+        }
+    }
+}
+
+/// An effect an action applies when it lands, lasting for the following `duration` rounds.
+#[derive(InspectorOptions, Reflect, Deserialize, Clone, Copy, Debug)]
+#[reflect(InspectorOptions)]
+pub struct StatusEffect {
+    pub effect: StatusEffectType,
+    pub duration: u8,
+    /// Applies the effect to whoever took the action rather than its target.
+    #[serde(default)]
+    pub on_self: bool,
+}
+
+/// The status effects an action applies when it lands.
+#[derive(Component, Clone, Debug, Default)]
+pub struct AppliesEffects(pub Vec<StatusEffect>);
+
+#[derive(Reflect, InspectorOptions, Clone, Copy, Debug)]
+pub struct ActiveEffect {
+    pub effect: StatusEffectType,
+    pub remaining: u8,
+    /// Effects don't tick down in the round they're applied.
+    fresh: bool,
+}
+
+#[derive(Component, Reflect, InspectorOptions, Default, Clone, Debug)]
+pub struct StatusEffects(pub Vec<ActiveEffect>);
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: &StatusEffect) {
+        self.0.push(ActiveEffect {
+            effect: effect.effect,
+            remaining: effect.duration,
+            fresh: true,
+        });
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.0.iter().any(|e| e.effect == StatusEffectType::Stun)
+    }
+
+    pub fn is_weakened(&self) -> bool {
+        self.0
+            .iter()
+            .any(|e| e.effect == StatusEffectType::Weakened)
+    }
+
+    /// Uses up shields against incoming damage, returning the damage left over.
+    pub fn absorb(&mut self, damage: usize) -> usize {
+        let mut damage = damage;
+        for effect in self.0.iter_mut() {
+            if let StatusEffectType::Shield { amount } = &mut effect.effect {
+                let absorbed = (*amount as usize).min(damage);
+                *amount -= absorbed as u8;
+                damage -= absorbed;
+            }
+        }
+        self.0
+            .retain(|e| !matches!(e.effect, StatusEffectType::Shield { amount: 0 }));
+        damage
+    }
+
+    /// Advances every effect by a round, returning the damage taken from bleeding.
+    fn tick(&mut self) -> usize {
+        let mut damage = 0;
+        for effect in self.0.iter_mut() {
+            if effect.fresh {
+                effect.fresh = false;
+                continue;
+            }
+            if let StatusEffectType::Bleed { damage: bleed } = effect.effect {
+                damage += bleed as usize;
+            }
+            effect.remaining = effect.remaining.saturating_sub(1);
+        }
+        self.0.retain(|e| e.remaining > 0);
+        damage
+    }
+}
+
+type LandedEffectsQuery<'w, 's, 'a> = Query<
+    'w,
+    's,
+    (
+        &'a AppliesEffects,
+        &'a Resolution,
+        &'a ActionTarget,
+        Option<&'a Parent>,
+        Has<ChallengerAction>,
+    ),
+    Added<ActiveResolution>,
+>;
+
+fn apply_status_effects(resolution: LandedEffectsQuery, mut affected: Query<&mut StatusEffects>) {
+    let Ok((effects, resolution, target, actor, is_challenger)) = resolution.get_single() else {
+        return;
+    };
+    if !resolution.result.lands(is_challenger) {
+        return;
+    }
+    for effect in effects.0.iter() {
+        let entities = if effect.on_self {
+            actor.map(|p| p.get()).into_iter().collect()
+        } else {
+            target.0.clone()
+        };
+        for entity in entities {
+            if let Ok(mut status) = affected.get_mut(entity) {
+                status.apply(effect);
+            }
+        }
+    }
+}
+
+fn tick_status_effects(
+    mut commands: Commands,
+    mut affected: Query<(
+        Entity,
+        &mut StatusEffects,
+        Option<&mut CurrentHealth>,
+        Has<Player>,
+        Has<Challenger>,
+    )>,
+) {
+    for (entity, mut status, health, is_player, is_challenger) in affected.iter_mut() {
+        let damage = status.tick();
+        let Some(mut health) = health else {
+            continue;
+        };
+        if damage == 0 {
+            continue;
+        }
+        health.0 = health.0.saturating_sub(damage);
+        if health.0 == 0 {
+            if is_player {
+                commands.insert_resource(NextState(Some(GameState::Failed)));
+            } else if is_challenger {
+                commands.entity(entity).insert(ChallengerCompleted);
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use bevy_turborand::{DelegatedRng, TurboRand};
+use bevy_ui_dsl::{text, UiChildBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    storage,
+    ui::{
+        buttons::focus_text_button, classes::*,
+        intermediary_node_bundles::IntoIntermediaryNodeBundle,
+    },
+};
+
+use super::{
+    difficulty::{Difficulty, DifficultyMultipliers},
+    encounter::{
+        encounter_log::{EncounterLog, LogFormat},
+        powers::Power,
+    },
+    factions::Faction,
+    game_state::GameState,
+    mission::mission_types::Missions,
+    run_rng::{checkpoint, RngStream, RunRng},
+    story::{Phase, PhaseRound, Story, StoryProgress},
+};
+
+const HIGH_SCORES_KEY: &str = "the-just-two-high-scores";
+const MAX_HIGH_SCORES: usize = 10;
+
+/// How much harder challengers get with every endless phase.
+const ESCALATION: f32 = 0.1;
+
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EndlessRun>()
+            .add_systems(OnExit(AppState::InGame), record_high_score);
+    }
+}
+
+/// Present once the story is over and the heroes have chosen to keep going.
+#[derive(Resource, Reflect, InspectorOptions, Default, Clone, Serialize, Deserialize)]
+#[reflect(Resource, InspectorOptions)]
+pub struct EndlessRun {
+    /// How many endless phases have been started.
+    pub depth: usize,
+}
+
+impl EndlessRun {
+    /// Moves the run on to the next endless phase.
+    pub fn start_next_phase(
+        &mut self,
+        story: &Story,
+        missions: &Missions,
+        run_rng: &mut RunRng,
+        commands: &mut Commands,
+    ) {
+        let round = PhaseRound(0, self.phase_index(story));
+        let rng = run_rng
+            .stream(RngStream::Story, &checkpoint(&round, None))
+            .get_mut();
+        let phase = self.next_phase(story, missions, rng);
+        commands.insert_resource(PhaseRound(0, self.phase_index(story)));
+        commands.insert_resource(phase);
+    }
+
+    /// Builds the next phase, drawing on every mission and the story's last rewards.
+    fn next_phase(
+        &mut self,
+        story: &Story,
+        missions: &Missions,
+        rng: &mut impl TurboRand,
+    ) -> Phase {
+        self.depth += 1;
+        let mut keys = missions.0.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        let min_missions = 1 + self.depth / 3;
+        Phase {
+            key: format!("endless_{}", self.depth),
+            min_missions,
+            max_missions: min_missions + 1,
+            simulatneous_missions: rng.usize(2..=3).min(keys.len()),
+            missions: keys,
+            rewards: story.phases.iter().rev().find_map(|p| p.rewards.clone()),
+            next: vec![],
+        }
+    }
+
+    /// The phase index endless phases are counted from, so their random streams don't repeat.
+    pub fn phase_index(&self, story: &Story) -> usize {
+        story.phases.len() + self.depth
+    }
+
+    /// Scales the chosen difficulty up for the current depth.
+    pub fn difficulty(&self, base: &Difficulty) -> Difficulty {
+        let multipliers = base.multipliers();
+        let escalation = ESCALATION * self.depth as f32;
+        Difficulty::Custom(DifficultyMultipliers {
+            health: multipliers.health * (1. + escalation),
+            damage: multipliers.damage * (1. + escalation),
+            thresholds: multipliers.thresholds * (1. + escalation / 2.),
+        })
+    }
+}
+
+/// The difficulty challengers are spawned with, including any endless escalation.
+pub fn effective_difficulty(difficulty: &Difficulty, endless: Option<&EndlessRun>) -> Difficulty {
+    endless.map_or(*difficulty, |endless| endless.difficulty(difficulty))
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighScore {
+    pub story: String,
+    pub score: usize,
+    pub missions: usize,
+    pub powers: usize,
+    #[serde(default)]
+    pub endless_phases: usize,
+}
+
+impl HighScore {
+    pub fn new(
+        story: &Story,
+        progress: &StoryProgress,
+        powers: usize,
+        endless: Option<&EndlessRun>,
+    ) -> Self {
+        let missions = progress.completed_missions.len();
+        Self {
+            story: story.title.clone(),
+            score: missions * 100 + powers * 25,
+            missions,
+            powers,
+            endless_phases: endless.map(|e| e.depth).unwrap_or_default(),
+        }
+    }
+}
+
+/// The best scores on this machine, highest first.
+#[derive(Default, Serialize, Deserialize)]
+pub struct HighScores(pub Vec<HighScore>);
+
+impl HighScores {
+    pub fn load() -> Self {
+        storage::read(HIGH_SCORES_KEY)
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether a score would make it onto the table.
+    pub fn qualifies(&self, score: usize) -> bool {
+        self.0.len() < MAX_HIGH_SCORES || self.0.iter().any(|s| s.score < score)
+    }
+
+    fn record(&mut self, entry: HighScore) {
+        self.0.push(entry);
+        self.0.sort_by(|a, b| b.score.cmp(&a.score));
+        self.0.truncate(MAX_HIGH_SCORES);
+        match serde_json::to_string(&self.0) {
+            Ok(data) => storage::write(HIGH_SCORES_KEY, &data),
+            Err(e) => error!("Couldn't save high scores: {e}"),
+        }
+    }
+
+    pub fn display(&self, p: &mut UiChildBuilder) {
+        text("High Scores", primary_box_item.nb(), standard_text, p);
+        for (rank, entry) in self.0.iter().enumerate() {
+            let endless = match entry.endless_phases {
+                0 => String::new(),
+                phases => format!(", {phases} endless phases"),
+            };
+            text(
+                format!(
+                    "{}. {} - {} ({} missions, {} powers{endless})",
+                    rank + 1,
+                    entry.score,
+                    entry.story,
+                    entry.missions,
+                    entry.powers
+                ),
+                primary_box_item.nb(),
+                standard_text,
+                p,
+            );
+        }
+    }
+}
+
+/// Shows how the run scored against the high score table, with buttons to export the
+/// encounter log. Returns each export button along with the format it writes.
+pub fn display_run_summary(
+    story: Option<&Story>,
+    progress: Option<&StoryProgress>,
+    powers: usize,
+    endless: Option<&EndlessRun>,
+    log: Option<&EncounterLog>,
+    p: &mut UiChildBuilder,
+) -> Vec<(Entity, LogFormat)> {
+    let high_scores = HighScores::load();
+    if let (Some(story), Some(progress)) = (story, progress) {
+        let score = HighScore::new(story, progress, powers, endless).score;
+        let label = if high_scores.qualifies(score) {
+            format!("Score: {score} - a new high score!")
+        } else {
+            format!("Score: {score}")
+        };
+        text(label, primary_box_item.nb(), standard_text, p);
+    }
+    let mut export_buttons = vec![];
+    if log.is_some_and(|log| !log.entries.is_empty()) {
+        for format in [LogFormat::Text, LogFormat::Json] {
+            let button = focus_text_button(
+                format.label(),
+                (c_button.nb(), primary_box_item.nb()),
+                apply_button_state,
+                button_text,
+                p,
+            );
+            export_buttons.push((button, format));
+        }
+    }
+    high_scores.display(p);
+    export_buttons
+}
+
+/// Runs that end in failure or completion are scored as the player leaves them.
+/// Runs left from the pause menu are still saved, so they aren't scored yet.
+fn record_high_score(
+    state: Res<State<GameState>>,
+    story: Option<Res<Story>>,
+    progress: Option<Res<StoryProgress>>,
+    endless: Option<Res<EndlessRun>>,
+    powers: Query<(), (With<Power>, With<Faction>)>,
+) {
+    if !matches!(state.get(), GameState::Failed | GameState::Complete) {
+        return;
+    }
+    let (Some(story), Some(progress)) = (story, progress) else {
+        return;
+    };
+    let entry = HighScore::new(&story, &progress, powers.iter().count(), endless.as_deref());
+    HighScores::load().record(entry);
+}
@@ -1,8 +1,29 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::InspectorOptions;
-use serde::Deserialize;
-#[derive(Debug, Clone, Copy, Reflect, Deserialize, InspectorOptions)]
+use serde::{Deserialize, Serialize};
+/// Which of the two heroes something belongs to. Powers carry it as a component
+/// to mark whose inventory they're in.
+#[derive(
+    Component, Debug, Clone, Copy, PartialEq, Eq, Reflect, Deserialize, Serialize, InspectorOptions,
+)]
 pub enum Faction {
     Knights,
     Druids,
 }
+
+impl Faction {
+    /// The key of this faction's hero in `players.pl.yaml`.
+    pub fn player_key(&self) -> &'static str {
+        match self {
+            Faction::Knights => "player_knight",
+            Faction::Druids => "player_druid",
+        }
+    }
+
+    pub fn other(&self) -> Self {
+        match self {
+            Faction::Knights => Faction::Druids,
+            Faction::Druids => Faction::Knights,
+        }
+    }
+}
@@ -4,6 +4,7 @@ use bevy_ui_dsl::*;
 
 use crate::{
     app_state::AppState,
+    assets::MainGameAssets,
     ui::{
         buttons::{focus_text_button, focused_button_activated, TypedFocusedButtonQuery},
         classes::*,
@@ -11,7 +12,18 @@ use crate::{
     },
 };
 
-use super::game_state::GameState;
+use super::{
+    encounter::{
+        encounter_log::{show_exported, EncounterLog, LogFormat},
+        powers::Power,
+    },
+    endless::{display_run_summary, EndlessRun},
+    factions::Faction,
+    game_state::GameState,
+    mission::mission_types::Missions,
+    run_rng::RunRng,
+    story::{Ending, Story, StoryProgress},
+};
 pub struct GameCompletedPlugin;
 
 impl Plugin for GameCompletedPlugin {
@@ -33,16 +45,44 @@ impl Plugin for GameCompletedPlugin {
 struct Screen;
 
 #[derive(Component)]
-struct Button;
+enum Button {
+    KeepGoing,
+    ExportLog(LogFormat),
+    Menu,
+}
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+#[allow(clippy::too_many_arguments)]
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    ending: Option<Res<Ending>>,
+    story: Option<Res<Story>>,
+    progress: Option<Res<StoryProgress>>,
+    endless: Option<Res<EndlessRun>>,
+    powers: Query<(), (With<Power>, With<Faction>)>,
+    log: Option<Res<EncounterLog>>,
+) {
+    let mut keep_going_button = None;
     let mut menu_button = None;
+    let mut export_buttons = vec![];
     let r = root((overlay, c_root), &asset_server, &mut commands, |p| {
         node(primary_box, p, |p| {
             node((span.nb(), primary_box_main.nb()), p, |p| {
                 text("Game", (), (main_text, knight_text), p);
                 text("Completed!", (), (main_text, druid_text), p);
             });
+            if let Some(ending) = &ending {
+                text(&ending.title, primary_box_item.nb(), main_text, p);
+                text(&ending.text, primary_box_item.nb(), standard_text, p);
+            }
+            focus_text_button(
+                "Keep Going",
+                (c_button.nb(), primary_box_item.nb()),
+                apply_button_state,
+                button_text,
+                p,
+            )
+            .set(&mut keep_going_button);
             focus_text_button(
                 "Main Menu",
                 (c_button.nb(), primary_box_item.nb()),
@@ -51,30 +91,72 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                 p,
             )
             .set(&mut menu_button);
+            export_buttons = display_run_summary(
+                story.as_deref(),
+                progress.as_deref(),
+                powers.iter().count(),
+                endless.as_deref(),
+                log.as_deref(),
+                p,
+            );
         });
     });
     commands.entity(r).insert(Screen);
-    commands.entity(menu_button.unwrap()).insert(Button);
+    commands
+        .entity(keep_going_button.unwrap())
+        .insert(Button::KeepGoing);
+    commands.entity(menu_button.unwrap()).insert(Button::Menu);
+    for (button, format) in export_buttons {
+        commands.entity(button).insert(Button::ExportLog(format));
+    }
 }
 
 fn exit(mut commands: Commands, query: Query<Entity, With<Screen>>) {
     for item in query.iter() {
         commands.entity(item).despawn_recursive();
     }
+    commands.remove_resource::<Ending>();
 }
 
+/// Keeping going starts endless phases from the mission pool, carrying on the run.
+#[allow(clippy::too_many_arguments)]
 fn process_input(
     In(focused): In<Option<Entity>>,
     mut commands: Commands,
     interaction_query: TypedFocusedButtonQuery<'_, '_, '_, Button>,
+    story: Option<Res<Story>>,
+    endless: Option<Res<EndlessRun>>,
+    assets: Res<MainGameAssets>,
+    missions: Res<Assets<Missions>>,
+    mut run_rng: ResMut<RunRng>,
+    log: Option<Res<EncounterLog>>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
 ) {
     let Some(focused) = focused else {
         return;
     };
-    let Some((_entity, _btn)) = interaction_query.get(focused).ok() else {
+    let Some((_entity, btn)) = interaction_query.get(focused).ok() else {
         return;
     };
-    commands.insert_resource(NextState(Some(AppState::MainMenu)));
+    match btn {
+        Button::KeepGoing => {
+            let (Some(story), Some(missions)) = (story, missions.get(&assets.missions)) else {
+                return;
+            };
+            let mut endless = endless.map(|e| e.clone()).unwrap_or_default();
+            endless.start_next_phase(&story, missions, &mut run_rng, &mut commands);
+            commands.insert_resource(endless);
+            commands.insert_resource(NextState(Some(GameState::WorldMap)));
+        }
+        Button::ExportLog(format) => {
+            let Some(file) = log.and_then(|log| log.export(*format)) else {
+                return;
+            };
+            show_exported(&file, focused, &children, &mut texts);
+        }
+        Button::Menu => commands.insert_resource(NextState(Some(AppState::MainMenu))),
+    }
 }
 
 fn process_keyboard_input(mut commands: Commands, keys: Res<Input<KeyCode>>) {
@@ -11,7 +11,16 @@ use crate::{
     },
 };
 
-use super::game_state::GameState;
+use super::{
+    encounter::{
+        encounter_log::{show_exported, EncounterLog, LogFormat},
+        powers::Power,
+    },
+    endless::{display_run_summary, EndlessRun},
+    factions::Faction,
+    game_state::GameState,
+    story::{Story, StoryProgress},
+};
 pub struct GameOverPlugin;
 
 impl Plugin for GameOverPlugin {
@@ -33,10 +42,22 @@ impl Plugin for GameOverPlugin {
 struct Screen;
 
 #[derive(Component)]
-struct Button;
+enum Button {
+    ExportLog(LogFormat),
+    Menu,
+}
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    story: Option<Res<Story>>,
+    progress: Option<Res<StoryProgress>>,
+    endless: Option<Res<EndlessRun>>,
+    powers: Query<(), (With<Power>, With<Faction>)>,
+    log: Option<Res<EncounterLog>>,
+) {
     let mut menu_button = None;
+    let mut export_buttons = vec![];
     let r = root((overlay, c_root), &asset_server, &mut commands, |p| {
         node(primary_box, p, |p| {
             node((span.nb(), primary_box_main.nb()), p, |p| {
@@ -51,10 +72,21 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                 p,
             )
             .set(&mut menu_button);
+            export_buttons = display_run_summary(
+                story.as_deref(),
+                progress.as_deref(),
+                powers.iter().count(),
+                endless.as_deref(),
+                log.as_deref(),
+                p,
+            );
         });
     });
     commands.entity(r).insert(Screen);
-    commands.entity(menu_button.unwrap()).insert(Button);
+    commands.entity(menu_button.unwrap()).insert(Button::Menu);
+    for (button, format) in export_buttons {
+        commands.entity(button).insert(Button::ExportLog(format));
+    }
 }
 
 fn exit(mut commands: Commands, query: Query<Entity, With<Screen>>) {
@@ -67,14 +99,25 @@ fn process_input(
     In(focused): In<Option<Entity>>,
     mut commands: Commands,
     interaction_query: TypedFocusedButtonQuery<'_, '_, '_, Button>,
+    log: Option<Res<EncounterLog>>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
 ) {
     let Some(focused) = focused else {
         return;
     };
-    let Some((_entity, _btn)) = interaction_query.get(focused).ok() else {
+    let Some((_entity, btn)) = interaction_query.get(focused).ok() else {
         return;
     };
-    commands.insert_resource(NextState(Some(AppState::MainMenu)));
+    match btn {
+        Button::ExportLog(format) => {
+            let Some(file) = log.and_then(|log| log.export(*format)) else {
+                return;
+            };
+            show_exported(&file, focused, &children, &mut texts);
+        }
+        Button::Menu => commands.insert_resource(NextState(Some(AppState::MainMenu))),
+    }
 }
 
 fn process_keyboard_input(mut commands: Commands, keys: Res<Input<KeyCode>>) {
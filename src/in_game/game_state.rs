@@ -1,7 +1,21 @@
 use bevy::prelude::*;
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Eq, PartialEq, Copy, Debug, Hash, Default, States, Reflect, InspectorOptions)]
+#[derive(
+    Clone,
+    Eq,
+    PartialEq,
+    Copy,
+    Debug,
+    Hash,
+    Default,
+    States,
+    Reflect,
+    InspectorOptions,
+    Serialize,
+    Deserialize,
+)]
 #[reflect(InspectorOptions)]
 pub enum GameState {
     #[default]
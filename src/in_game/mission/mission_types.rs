@@ -2,57 +2,272 @@ use bevy::{prelude::*, reflect::TypeUuid, utils::HashMap};
 use bevy_common_assets::yaml::YamlAssetPlugin;
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
 use bevy_turborand::TurboRand;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use crate::in_game::encounter::encounter_setup_types::{EncounterInitialDetails, Encounters};
+use crate::in_game::encounter::{
+    actions::ActionResult,
+    encounter_setup_types::{EncounterInitialDetails, Encounters},
+};
+
+use super::rewards::RewardTable;
 
 pub struct MissionAssetsPlugin;
 
 impl Plugin for MissionAssetsPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<Mission>()
+            .register_type::<MissionNode>()
+            .register_type::<MissionEdge>()
+            .register_type::<EdgeCondition>()
             .register_type::<Missions>()
             .register_type::<MissionStage>()
             .add_plugins(YamlAssetPlugin::<Missions>::new(&["ms.yaml"]));
     }
 }
 
-#[derive(Resource, Default, Reflect, InspectorOptions, Clone)]
+/// When an edge out of a node can be taken, based on how its encounter went.
+#[derive(Reflect, InspectorOptions, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[reflect(InspectorOptions)]
+pub enum EdgeCondition {
+    #[default]
+    Victory,
+    Fled,
+    /// The player took a text action with this title, getting one of these results.
+    /// `**` in the title matches any challenger's name.
+    Action {
+        title: String,
+        results: Vec<ActionResult>,
+    },
+}
+
+impl EdgeCondition {
+    pub fn met(&self, stage: &MissionStage) -> bool {
+        match self {
+            EdgeCondition::Victory => !stage.fled,
+            EdgeCondition::Fled => stage.fled,
+            EdgeCondition::Action { title, results } => stage
+                .results
+                .iter()
+                .any(|(taken, result)| title_matches(title, taken) && results.contains(result)),
+        }
+    }
+
+    pub fn label(&self) -> Option<String> {
+        match self {
+            EdgeCondition::Victory => None,
+            EdgeCondition::Fled => Some("if fled".to_string()),
+            EdgeCondition::Action { title, results } => Some(format!(
+                "if {} - {}",
+                title.replace("**", "..."),
+                results
+                    .iter()
+                    .map(|r| format!("{r:?}"))
+                    .collect::<Vec<_>>()
+                    .join(" or ")
+            )),
+        }
+    }
+}
+
+fn title_matches(pattern: &str, title: &str) -> bool {
+    let parts = pattern.split("**").collect::<Vec<_>>();
+    let [first, middle @ .., last] = parts.as_slice() else {
+        return pattern == title;
+    };
+    let Some(mut rest) = title.strip_prefix(first) else {
+        return false;
+    };
+    for part in middle {
+        let Some(index) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[index + part.len()..];
+    }
+    rest.ends_with(last)
+}
+
+#[derive(Reflect, InspectorOptions, Clone, Debug, Default, Serialize, Deserialize)]
+#[reflect(InspectorOptions)]
+pub struct MissionEdge {
+    pub to: String,
+    #[serde(default)]
+    pub when: EdgeCondition,
+}
+
+#[derive(Reflect, InspectorOptions, Clone, Default, Serialize, Deserialize)]
+#[reflect(InspectorOptions)]
+pub struct MissionNode {
+    pub key: String,
+    pub encounter: EncounterInitialDetails,
+    pub edges: Vec<MissionEdge>,
+}
+
+#[derive(Resource, Default, Reflect, InspectorOptions, Clone, Serialize, Deserialize)]
 #[reflect(Resource, InspectorOptions)]
 pub struct Mission {
+    #[serde(default)]
+    pub key: String,
     pub title: String,
-    pub encounters: Vec<Vec<EncounterInitialDetails>>,
+    /// The nodes the player can begin the mission with.
+    pub start: Vec<String>,
+    pub nodes: Vec<MissionNode>,
+    #[serde(default)]
+    pub rewards: Option<RewardTable>,
+}
+
+impl Mission {
+    pub fn node(&self, key: &str) -> Option<&MissionNode> {
+        self.nodes.iter().find(|node| node.key == key)
+    }
+
+    /// The nodes the player can move on to. The mission is over once there are none.
+    pub fn open_nodes(&self, stage: &MissionStage) -> Vec<&MissionNode> {
+        let keys = match stage.path.last() {
+            None => self.start.iter().collect::<Vec<_>>(),
+            Some(current) => self
+                .node(current)
+                .into_iter()
+                .flat_map(|node| node.edges.iter())
+                .filter(|edge| edge.when.met(stage))
+                .map(|edge| &edge.to)
+                .collect(),
+        };
+        let mut open: Vec<&MissionNode> = vec![];
+        for node in keys.into_iter().filter_map(|key| self.node(key)) {
+            if !open.iter().any(|n| n.key == node.key) {
+                open.push(node);
+            }
+        }
+        open
+    }
+
+    /// Every node that can still be reached from `from`, whatever the outcomes.
+    pub fn reachable(&self, from: &[&MissionNode]) -> Vec<&str> {
+        let mut reached = from.iter().map(|n| n.key.as_str()).collect::<Vec<_>>();
+        let mut index = 0;
+        while let Some(key) = reached.get(index).copied() {
+            for edge in self.node(key).iter().flat_map(|n| n.edges.iter()) {
+                if !reached.contains(&edge.to.as_str()) {
+                    reached.push(edge.to.as_str());
+                }
+            }
+            index += 1;
+        }
+        reached
+    }
+
+    /// Groups the nodes into columns by how many steps they are from the start.
+    pub fn columns(&self) -> Vec<Vec<&MissionNode>> {
+        let mut depths = self
+            .start
+            .iter()
+            .map(|key| (key.as_str(), 0))
+            .collect::<Vec<_>>();
+        let mut index = 0;
+        while let Some((key, depth)) = depths.get(index).copied() {
+            for edge in self.node(key).iter().flat_map(|n| n.edges.iter()) {
+                if !depths.iter().any(|(k, _)| *k == edge.to) {
+                    depths.push((edge.to.as_str(), depth + 1));
+                }
+            }
+            index += 1;
+        }
+        let mut columns: Vec<Vec<&MissionNode>> = vec![];
+        for node in self.nodes.iter() {
+            let Some((_, depth)) = depths.iter().find(|(k, _)| *k == node.key) else {
+                continue;
+            };
+            if columns.len() <= *depth {
+                columns.resize_with(depth + 1, Vec::new);
+            }
+            columns[*depth].push(node);
+        }
+        columns
+    }
 }
 
-#[derive(Resource, Default, Reflect, InspectorOptions)]
+/// The player's progress through the mission graph.
+#[derive(Resource, Default, Reflect, InspectorOptions, Clone, Serialize, Deserialize)]
 #[reflect(Resource, InspectorOptions)]
-pub struct MissionStage(pub usize);
+pub struct MissionStage {
+    /// The nodes entered so far, in order.
+    pub path: Vec<String>,
+    /// Whether the player fled the encounter at the end of the path.
+    pub fled: bool,
+    /// The text actions the player took in that encounter, and how they went.
+    pub results: Vec<(String, ActionResult)>,
+}
+
+impl MissionStage {
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    pub fn enter(&mut self, key: &str) {
+        self.path.push(key.to_string());
+        self.fled = false;
+        self.results.clear();
+    }
+}
+
+#[derive(Default, Reflect, InspectorOptions, Deserialize)]
+#[reflect(InspectorOptions)]
+pub struct MissionNodeInfo {
+    pub key: String,
+    /// One of these is picked for the node when the mission is generated.
+    pub encounters: Vec<String>,
+    #[serde(default)]
+    pub edges: Vec<MissionEdge>,
+    /// The threat budget for generated challengers at this node.
+    #[serde(default)]
+    pub budget: Option<usize>,
+}
 
 #[derive(Default, Reflect, InspectorOptions, Deserialize)]
 #[reflect(InspectorOptions)]
 pub struct MissionGenerationInfo {
     pub titles: Vec<String>,
-    pub encounters: Vec<Vec<String>>,
+    pub start: Vec<String>,
+    pub nodes: Vec<MissionNodeInfo>,
+    /// The named map locations the mission can be offered at. Anywhere the heroes know of if empty.
+    #[serde(default)]
+    pub locations: Vec<String>,
+    /// Overrides the rewards of the story phase the mission is offered in.
+    #[serde(default)]
+    pub rewards: Option<RewardTable>,
 }
 
 impl MissionGenerationInfo {
-    pub fn mission(&self, rng: &mut impl TurboRand, encounters: &Encounters) -> Mission {
+    pub fn mission(&self, key: &str, rng: &mut impl TurboRand, encounters: &Encounters) -> Mission {
         let title = rng
             .sample(&self.titles)
             .cloned()
             .unwrap_or("Mission".to_string());
-        let encounters = self
-            .encounters
+        let nodes = self
+            .nodes
             .iter()
-            .map(|encounters| rng.sample_multiple(encounters, 2))
-            .map(|encounter_names| {
-                encounter_names
-                    .iter()
-                    .filter_map(|encounter_name| encounters.0.get(encounter_name.as_str()).cloned())
-                    .collect()
+            .filter_map(|node| {
+                let mut encounter = rng
+                    .sample(&node.encounters)
+                    .and_then(|key| encounters.0.get(key.as_str()))
+                    .cloned()?;
+                if let (Some(budget), Some(pool)) = (node.budget, encounter.generated.as_mut()) {
+                    pool.budget = budget;
+                }
+                Some(MissionNode {
+                    key: node.key.clone(),
+                    encounter,
+                    edges: node.edges.clone(),
+                })
             })
             .collect();
-        Mission { title, encounters }
+        Mission {
+            key: key.to_string(),
+            title,
+            start: self.start.clone(),
+            nodes,
+            rewards: self.rewards.clone(),
+        }
     }
 }
 
@@ -1,10 +1,12 @@
 pub mod mission_types;
+pub mod rewards;
 
 use bevy::prelude::*;
 
 use bevy_inspector_egui::InspectorOptions;
-use bevy_turborand::{DelegatedRng, GlobalRng, TurboRand};
-use bevy_ui_dsl::{node, root, text};
+use bevy_turborand::DelegatedRng;
+use bevy_ui_dsl::{node, root, text, UiChildBuilder};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     assets::MainGameAssets,
@@ -16,17 +18,20 @@ use crate::{
     },
 };
 
-use self::mission_types::{Mission, MissionAssetsPlugin, MissionStage};
+use self::{
+    mission_types::{Mission, MissionAssetsPlugin, MissionStage},
+    rewards::{Reward, RewardTable, WeightedReward},
+};
 
 use super::{
-    encounter::{
-        dice_pools::DiceType,
-        encounter_setup_types::{self},
-        powers::Power,
-    },
+    encounter::{actions::Fled, heroes::Heroes},
+    factions::Faction,
     game_state::GameState,
-    story::PhaseRound,
-    InGameUpdate,
+    run_rng::{checkpoint, RngStream, RunRng},
+    save::SaveRequested,
+    story::{Phase, PhaseRound, StoryProgress},
+    world_map::map_types::WorldMapState,
+    InGameUpdate, Players,
 };
 
 pub struct MissionPlugin;
@@ -35,14 +40,25 @@ impl Plugin for MissionPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(MissionAssetsPlugin)
             .register_type::<UiButton>()
+            .register_type::<Reward>()
+            .register_type::<WeightedReward>()
+            .register_type::<RewardTable>()
             .add_systems(
                 OnEnter(GameState::Mission),
-                (draw_encounter_selection_ui, draw_completed_ui),
+                (
+                    record_flight,
+                    (draw_encounter_selection_ui, draw_completed_ui, draw_fled_ui),
+                )
+                    .chain(),
             )
             .add_systems(OnExit(GameState::Mission), clear_world_map)
             .add_systems(
                 InGameUpdate,
-                (focused_button_activated.pipe(process_input)).run_if(in_state(GameState::Mission)),
+                (
+                    focused_button_activated.pipe(process_input),
+                    focused_button_activated.pipe(pick_reward),
+                )
+                    .run_if(in_state(GameState::Mission)),
             );
     }
 }
@@ -51,12 +67,40 @@ impl Plugin for MissionPlugin {
 pub struct MissionEntity;
 
 #[derive(Component, Reflect, InspectorOptions)]
-pub struct UiButton(Option<encounter_setup_types::EncounterInitialDetails>);
+pub struct UiButton(Option<String>);
+
+#[derive(Component)]
+struct RewardChoice(Reward);
+
+#[derive(Component)]
+struct PicksRemaining;
+
+/// The rewards still on offer, and who receives each of the ones still to be picked, in order.
+#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+pub struct RewardPicks {
+    offered: Vec<Reward>,
+    recipients: Vec<Faction>,
+}
+
+impl RewardPicks {
+    pub fn is_empty(&self) -> bool {
+        self.recipients.is_empty()
+    }
+}
 
 fn clear_world_map(mut commands: Commands, mission_entities: Query<Entity, With<MissionEntity>>) {
     for entity in mission_entities.iter() {
         commands.entity(entity).despawn_recursive();
     }
+    commands.remove_resource::<Fled>();
+    commands.remove_resource::<RewardPicks>();
+}
+
+/// Fleeing can lead somewhere in the mission graph, so it's kept with the rest of the stage.
+fn record_flight(fled: Option<Res<Fled>>, mission_stage: Option<ResMut<MissionStage>>) {
+    if let (Some(_), Some(mut mission_stage)) = (fled, mission_stage) {
+        mission_stage.fled = true;
+    }
 }
 
 fn draw_encounter_selection_ui(
@@ -65,9 +109,12 @@ fn draw_encounter_selection_ui(
     mission: Res<Mission>,
     mission_stage: Res<MissionStage>,
 ) {
-    let Some(encounters) = mission.encounters.get(mission_stage.0) else {
+    let open = mission.open_nodes(&mission_stage);
+    if open.is_empty() {
         return;
-    };
+    }
+    let reachable = mission.reachable(&open);
+    let current = mission_stage.path.last();
 
     let title = mission.title.as_str();
 
@@ -78,58 +125,115 @@ fn draw_encounter_selection_ui(
             node(mission_encounter_title.nb(), p, |p| {
                 text(title, (), main_text, p);
             });
+            if let Some(here) = current
+                .and_then(|key| mission.node(key))
+                .and_then(|n| n.encounter.title.as_ref())
+            {
+                text(format!("You are at {here}"), (), standard_text, p);
+            }
 
-            buttons = encounters
-                .iter()
-                .map(|encounter| {
-                    let button = focus_button(
-                        match encounter.player_faction {
-                            super::factions::Faction::Knights => encounter_knight_listing,
-                            super::factions::Faction::Druids => encounter_druid_listing,
-                        }
-                        .nb(),
-                        match encounter.player_faction {
-                            super::factions::Faction::Knights => apply_encounter_knight_state,
-                            super::factions::Faction::Druids => apply_encounter_druid_state,
-                        },
-                        p,
-                        |p| {
-                            text(
-                                encounter.title.clone().unwrap_or("Encounter".to_string()),
-                                (),
-                                (
-                                    standard_text,
-                                    button_text,
-                                    match encounter.player_faction {
-                                        super::factions::Faction::Knights => knight_text,
-                                        super::factions::Faction::Druids => druid_text,
+            node(mission_graph, p, |p| {
+                for column in mission.columns() {
+                    node(mission_graph_column, p, |p| {
+                        for mission_node in column {
+                            let faction = mission_node.encounter.player_faction;
+                            let contents = |p: &mut UiChildBuilder| {
+                                text(
+                                    mission_node
+                                        .encounter
+                                        .title
+                                        .clone()
+                                        .unwrap_or("Encounter".to_string()),
+                                    (),
+                                    (
+                                        standard_text,
+                                        button_text,
+                                        match faction {
+                                            Faction::Knights => knight_text,
+                                            Faction::Druids => druid_text,
+                                        },
+                                    ),
+                                    p,
+                                );
+                                for edge in mission_node.edges.iter() {
+                                    let to = mission
+                                        .node(&edge.to)
+                                        .and_then(|n| n.encounter.title.clone())
+                                        .unwrap_or(edge.to.clone());
+                                    let label = match edge.when.label() {
+                                        Some(condition) => format!("-> {to} ({condition})"),
+                                        None => format!("-> {to}"),
+                                    };
+                                    text(label, (), standard_text, p);
+                                }
+                            };
+
+                            if open.iter().any(|n| n.key == mission_node.key) {
+                                let button = focus_button(
+                                    match faction {
+                                        Faction::Knights => encounter_knight_listing,
+                                        Faction::Druids => encounter_druid_listing,
+                                    }
+                                    .nb(),
+                                    match faction {
+                                        Faction::Knights => apply_encounter_knight_state,
+                                        Faction::Druids => apply_encounter_druid_state,
                                     },
-                                ),
-                                p,
-                            );
-                        },
-                    );
-                    (button, encounter)
-                })
-                .collect();
+                                    p,
+                                    contents,
+                                );
+                                buttons.push((button, mission_node.key.clone()));
+                            } else if current == Some(&mission_node.key) {
+                                node(mission_node_current, p, contents);
+                            } else if mission_stage.path.contains(&mission_node.key) {
+                                node(mission_node_visited, p, contents);
+                            } else if reachable.contains(&mission_node.key.as_str()) {
+                                node(mission_node_reachable, p, contents);
+                            } else {
+                                node(mission_node_closed, p, contents);
+                            }
+                        }
+                    });
+                }
+            });
         });
     });
     commands.entity(r).insert(MissionEntity);
-    for (button, encounter) in buttons.into_iter() {
-        commands
-            .entity(button)
-            .insert(UiButton(Some(encounter.clone())));
+    for (button, key) in buttons.into_iter() {
+        commands.entity(button).insert(UiButton(Some(key)));
     }
 }
 
+/// Moves the story on from a mission the player is leaving. A mission they fled
+/// from doesn't count as completed.
+pub fn finish_mission(
+    mission: &Mission,
+    stage: &MissionStage,
+    phase_round: &mut PhaseRound,
+    progress: &mut StoryProgress,
+    map_state: Option<&mut WorldMapState>,
+) {
+    phase_round.0 += 1;
+    if !stage.fled {
+        progress.completed_missions.push(mission.key.clone());
+        if let Some(map_state) = map_state {
+            map_state.complete_current();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn process_input(
     In(focused): In<Option<Entity>>,
     mut commands: Commands,
     interaction_query: TypedFocusedButtonQuery<'_, '_, '_, UiButton>,
-    mission_stage: Option<Res<MissionStage>>,
+    mission: Option<Res<Mission>>,
+    mission_stage: Option<ResMut<MissionStage>>,
     mut phase_round: ResMut<PhaseRound>,
+    mut progress: ResMut<StoryProgress>,
+    mut map_state: Option<ResMut<WorldMapState>>,
 ) {
-    let Some(mission_stage) = mission_stage else {
+    let (Some(mission), Some(mut mission_stage)) = (mission, mission_stage) else {
         return;
     };
     let Some(focused) = focused else {
@@ -138,28 +242,39 @@ fn process_input(
     let Some((_, btn)) = interaction_query.get(focused).ok() else {
         return;
     };
-    if let Some(mission) = &btn.0 {
-        commands.insert_resource(mission.clone());
-        commands.insert_resource(MissionStage(mission_stage.0 + 1));
+    if let Some(mission_node) = btn.0.as_ref().and_then(|key| mission.node(key)) {
+        commands.insert_resource(mission_node.encounter.clone());
+        mission_stage.enter(&mission_node.key);
         commands.insert_resource(NextState(Some(GameState::Encounter)));
     } else {
+        finish_mission(
+            &mission,
+            &mission_stage,
+            &mut phase_round,
+            &mut progress,
+            map_state.as_deref_mut(),
+        );
         commands.remove_resource::<Mission>();
         commands.remove_resource::<MissionStage>();
-        phase_round.0 += 1;
         commands.insert_resource(NextState(Some(GameState::WorldMap)));
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_completed_ui(
     mission_stage: Res<MissionStage>,
     mission: Res<Mission>,
+    phase: Res<Phase>,
     assets: Res<MainGameAssets>,
     asset_server: Res<AssetServer>,
-    mut phase_round: ResMut<PhaseRound>,
+    players: Res<Assets<Players>>,
+    phase_round: Res<PhaseRound>,
     mut commands: Commands,
-    mut global_rng: ResMut<GlobalRng>,
+    mut run_rng: ResMut<RunRng>,
+    mut heroes: Option<ResMut<Heroes>>,
+    resumed_picks: Option<Res<RewardPicks>>,
 ) {
-    if mission_stage.0 < mission.encounters.len() {
+    if mission_stage.fled || !mission.open_nodes(&mission_stage).is_empty() {
         return;
     }
 
@@ -167,40 +282,46 @@ fn draw_completed_ui(
 
     let mut buttons = None;
 
-    let mut rng = global_rng.get_mut();
-    let new_powers = rng.sample_multiple(
-        &[
-            Power::AddDice(DiceType::D4),
-            Power::AddDice(DiceType::D4),
-            Power::AddDice(DiceType::D4),
-            Power::AddDice(DiceType::D4),
-            Power::AddDice(DiceType::D6),
-            Power::AddDice(DiceType::D6),
-            Power::AddDice(DiceType::D8),
-            Power::AddDice(DiceType::D12),
-            Power::Advantage,
-            Power::Advantage,
-            Power::Advantage,
-            Power::Advantage,
-            Power::Advantage,
-            Power::SplitDice,
-            Power::SplitDice,
-            Power::SplitDice,
-            Power::SplitDice,
-            Power::StaticBonus(1),
-            Power::StaticBonus(1),
-            Power::StaticBonus(1),
-            Power::StaticBonus(1),
-            Power::StaticBonus(2),
-            Power::StaticBonus(2),
-            Power::StaticBonus(3),
-        ],
-        4,
-    );
-
-    for item in new_powers.iter() {
-        commands.spawn(**item);
-    }
+    let mut granted = vec![];
+    let mut recipients = vec![];
+    let (offered, pick_recipients) = if let Some(picks) = resumed_picks {
+        // Resuming partway through picking, so everything else has already been granted.
+        (picks.offered.clone(), picks.recipients.clone())
+    } else {
+        let rng = run_rng
+            .stream(
+                RngStream::Rewards,
+                &checkpoint(&phase_round, Some(mission_stage.as_ref())),
+            )
+            .get_mut();
+        let table = mission
+            .rewards
+            .as_ref()
+            .or(phase.rewards.as_ref())
+            .cloned()
+            .unwrap_or_default();
+        let mut offered = table.offer(rng);
+        granted = table.guaranteed.clone();
+        if table.picks >= offered.len() {
+            granted.append(&mut offered);
+        }
+        let picks = table.picks.min(offered.len());
+
+        recipients = heroes
+            .as_ref()
+            .map(|heroes| heroes.reward_recipients(granted.len() + picks))
+            .unwrap_or_else(|| vec![Faction::Knights; granted.len() + picks]);
+        let pick_recipients = recipients.split_off(granted.len());
+        let players = players.get(&assets.players);
+        for (reward, faction) in granted.iter().zip(recipients.iter()) {
+            let hero = players.and_then(|players| players.get(faction.player_key()));
+            reward.grant(*faction, hero, heroes.as_deref_mut(), &mut commands);
+        }
+        (offered, pick_recipients)
+    };
+
+    let mut picks_label = None;
+    let mut choices = vec![];
 
     let r = root(mission_root, &asset_server, &mut commands, |p| {
         node(mission_container, p, |p| {
@@ -208,13 +329,49 @@ fn draw_completed_ui(
                 text(format!("{title} Complete"), (), (main_text, knight_text), p);
             });
 
-            text("Gained the following rewards:", (), standard_text, p);
-
-            node((), p, |p| {
-                for item in new_powers.iter() {
-                    item.display_bundle(&assets, 50., p);
+            for faction in [Faction::Knights, Faction::Druids] {
+                if !recipients.contains(&faction) {
+                    continue;
                 }
-            });
+                text(
+                    format!("The {faction:?} gained the following rewards:"),
+                    (),
+                    standard_text,
+                    p,
+                );
+
+                node(mission_rewards_container, p, |p| {
+                    for (item, _) in granted
+                        .iter()
+                        .zip(recipients.iter())
+                        .filter(|(_, recipient)| **recipient == faction)
+                    {
+                        item.display_bundle(&assets, 50., p);
+                    }
+                });
+            }
+
+            if !offered.is_empty() {
+                picks_label = Some(text(
+                    picks_remaining(&pick_recipients),
+                    (),
+                    standard_text,
+                    p,
+                ));
+                node(mission_rewards_container, p, |p| {
+                    for reward in offered.iter() {
+                        let button = focus_button(
+                            power_card_container.nb(),
+                            apply_power_card_state,
+                            p,
+                            |p| {
+                                reward.display_bundle(&assets, 50., p);
+                            },
+                        );
+                        choices.push((button, *reward));
+                    }
+                });
+            }
 
             buttons = Some(focus_button(
                 encounter_listing.nb(),
@@ -230,4 +387,117 @@ fn draw_completed_ui(
     if let Some(button) = buttons {
         commands.entity(button).insert(UiButton(None));
     }
+    if let Some(label) = picks_label {
+        commands.entity(label).insert(PicksRemaining);
+    }
+    for (button, reward) in choices {
+        commands.entity(button).insert(RewardChoice(reward));
+    }
+    commands.insert_resource(RewardPicks {
+        offered,
+        recipients: pick_recipients,
+    });
+}
+
+fn picks_remaining(recipients: &[Faction]) -> String {
+    match recipients.first() {
+        Some(next) => format!(
+            "Pick {} more - the next goes to the {next:?}",
+            recipients.len()
+        ),
+        None => "All rewards claimed".to_string(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pick_reward(
+    In(focused): In<Option<Entity>>,
+    mut commands: Commands,
+    interaction_query: TypedFocusedButtonQuery<'_, '_, '_, RewardChoice>,
+    choices: Query<Entity, With<RewardChoice>>,
+    mut labels: Query<&mut Text, With<PicksRemaining>>,
+    picks: Option<ResMut<RewardPicks>>,
+    mut heroes: Option<ResMut<Heroes>>,
+    players: Res<Assets<Players>>,
+    assets: Res<MainGameAssets>,
+) {
+    let (Some(focused), Some(mut picks)) = (focused, picks) else {
+        return;
+    };
+    let Ok((_, choice)) = interaction_query.get(focused) else {
+        return;
+    };
+    if picks.recipients.is_empty() {
+        return;
+    }
+    let faction = picks.recipients.remove(0);
+    if let Some(index) = picks.offered.iter().position(|r| *r == choice.0) {
+        picks.offered.remove(index);
+    }
+    let hero = players
+        .get(&assets.players)
+        .and_then(|players| players.get(faction.player_key()));
+    choice
+        .0
+        .grant(faction, hero, heroes.as_deref_mut(), &mut commands);
+
+    commands.insert_resource(SaveRequested);
+
+    commands.entity(focused).despawn_recursive();
+    if picks.recipients.is_empty() {
+        for entity in choices.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for mut text in labels.iter_mut() {
+        if let Some(section) = text.sections.get_mut(0) {
+            section.value = picks_remaining(&picks.recipients);
+        }
+    }
+}
+
+fn draw_fled_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mission: Res<Mission>,
+    mission_stage: Res<MissionStage>,
+) {
+    if !mission_stage.fled || !mission.open_nodes(&mission_stage).is_empty() {
+        return;
+    }
+    let title = mission.title.as_str();
+    let mut button = None;
+
+    let r = root(mission_root, &asset_server, &mut commands, |p| {
+        node(mission_container, p, |p| {
+            node(mission_encounter_title.nb(), p, |p| {
+                text(
+                    format!("{title} Abandoned"),
+                    (),
+                    (main_text, knight_text),
+                    p,
+                );
+            });
+
+            text(
+                "You fled, leaving the mission unfinished and its rewards unclaimed.",
+                (),
+                standard_text,
+                p,
+            );
+
+            button = Some(focus_button(
+                encounter_listing.nb(),
+                apply_encounter_state,
+                p,
+                |p| {
+                    text("Return to World Map", (), standard_text, p);
+                },
+            ));
+        });
+    });
+    commands.entity(r).insert(MissionEntity);
+    if let Some(button) = button {
+        commands.entity(button).insert(UiButton(None));
+    }
 }
@@ -0,0 +1,171 @@
+use bevy::prelude::*;
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use bevy_turborand::TurboRand;
+use bevy_ui_dsl::{node, text, UiChildBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assets::MainGameAssets,
+    in_game::{
+        encounter::{heroes::Heroes, player::PlayerReference, powers::Power},
+        factions::Faction,
+        grant_power,
+    },
+    ui::{classes::*, intermediary_node_bundles::IntoIntermediaryNodeBundle, DisplayBundle},
+};
+
+#[derive(InspectorOptions, Reflect, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[reflect(InspectorOptions)]
+pub enum Reward {
+    Power {
+        power: Power,
+    },
+    /// Restores health to the hero, up to their maximum.
+    Heal {
+        amount: usize,
+    },
+    /// Permanently raises the hero's maximum health.
+    MaxHealth {
+        amount: usize,
+    },
+}
+
+impl Default for Reward {
+    fn default() -> Self {
+        Self::Power {
+            power: Power::default(),
+        }
+    }
+}
+
+impl Reward {
+    pub fn grant(
+        &self,
+        faction: Faction,
+        hero: Option<&PlayerReference>,
+        heroes: Option<&mut Heroes>,
+        commands: &mut Commands,
+    ) {
+        let record = heroes.map(|heroes| heroes.get_mut(faction));
+        match self {
+            Reward::Power { power } => {
+                grant_power(commands, *power, faction);
+            }
+            Reward::Heal { amount } => {
+                let (Some(record), Some(hero)) = (record, hero) else {
+                    return;
+                };
+                let max = record.max_health(hero).0;
+                if let Some(health) = record.current_health.as_mut() {
+                    *health = (*health + amount).min(max);
+                }
+            }
+            Reward::MaxHealth { amount } => {
+                let Some(record) = record else {
+                    return;
+                };
+                record.bonus_max_health += amount;
+                if let Some(health) = record.current_health.as_mut() {
+                    *health += amount;
+                }
+            }
+        }
+    }
+}
+
+impl DisplayBundle for Reward {
+    fn display_bundle(&self, assets: &MainGameAssets, icon_size: f32, parent: &mut UiChildBuilder) {
+        match self {
+            Reward::Power { power } => power.display_bundle(assets, icon_size, parent),
+            Reward::Heal { amount } => {
+                node(reward_label.nb(), parent, |p| {
+                    text(format!("+{amount} HP"), (), (standard_text, button_text), p);
+                });
+            }
+            Reward::MaxHealth { amount } => {
+                node(reward_label.nb(), parent, |p| {
+                    text(
+                        format!("+{amount} Max HP"),
+                        (),
+                        (standard_text, button_text),
+                        p,
+                    );
+                });
+            }
+        }
+    }
+}
+
+#[derive(InspectorOptions, Reflect, Serialize, Deserialize, Default, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub struct WeightedReward {
+    pub reward: Reward,
+    /// Defaults to 1.
+    #[serde(default)]
+    pub weight: Option<f32>,
+}
+
+/// The rewards handed out when a mission is completed. `offered` rewards are
+/// drawn by weight, and the player picks `picks` of them.
+#[derive(InspectorOptions, Reflect, Serialize, Deserialize, Default, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub struct RewardTable {
+    pub offered: usize,
+    pub picks: usize,
+    /// Always granted, on top of whatever is picked.
+    #[serde(default)]
+    pub guaranteed: Vec<Reward>,
+    #[serde(default)]
+    pub rewards: Vec<WeightedReward>,
+}
+
+impl RewardTable {
+    /// Draws the offered rewards without replacement, in proportion to their weights.
+    pub fn offer(&self, rng: &mut impl TurboRand) -> Vec<Reward> {
+        let weight = |reward: &WeightedReward| reward.weight.unwrap_or(1.).max(0.);
+        let mut remaining = self.rewards.iter().collect::<Vec<_>>();
+        let mut offered = vec![];
+        while offered.len() < self.offered && !remaining.is_empty() {
+            let total: f32 = remaining.iter().map(|r| weight(r)).sum();
+            if total <= 0. {
+                break;
+            }
+            let mut target = rng.f32() * total;
+            let index = remaining
+                .iter()
+                .position(|r| {
+                    target -= weight(r);
+                    target <= 0.
+                })
+                .unwrap_or(remaining.len() - 1);
+            offered.push(remaining.remove(index).reward);
+        }
+        offered
+    }
+
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = vec![];
+        if self.picks > self.offered {
+            errors.push(format!(
+                "rewards pick {} but only offer {}",
+                self.picks, self.offered
+            ));
+        }
+        if self.offered > self.rewards.len() {
+            errors.push(format!(
+                "rewards offer {} but only list {}",
+                self.offered,
+                self.rewards.len()
+            ));
+        }
+        for reward in self.rewards.iter() {
+            if reward.weight.is_some_and(|w| w <= 0.) {
+                errors.push(format!(
+                    "reward {:?} is never offered - its weight is not above 0",
+                    reward.reward
+                ));
+            }
+        }
+        errors
+    }
+}
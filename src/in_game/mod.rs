@@ -1,12 +1,17 @@
+pub mod content;
+pub mod difficulty;
 pub mod encounter;
+pub mod endless;
 mod factions;
 mod game_completed;
 mod game_over;
 mod game_state;
 pub mod mission;
 mod pause_screen;
+pub mod run_rng;
+pub mod save;
 pub mod story;
-mod world_map;
+pub mod world_map;
 
 use bevy::{
     audio::{Volume, VolumeLevel},
@@ -15,18 +20,28 @@ use bevy::{
     prelude::*,
 };
 use bevy_inspector_egui::quick::StateInspectorPlugin;
+use bevy_turborand::{DelegatedRng, GlobalRng, TurboRand};
 
 use crate::{app_state::AppState, assets::MainGameAssets};
 
 use self::{
-    encounter::{dice_pools, powers::Power, sequencing::EncounterState, EncounterPlugin},
+    content::ContentValidationPlugin,
+    difficulty::DifficultyPlugin,
+    encounter::{
+        dice_pools, encounter_log::EncounterLog, heroes::Heroes, powers::Power,
+        sequencing::EncounterState, EncounterPlugin,
+    },
+    endless::{EndlessPlugin, EndlessRun},
+    factions::Faction,
     game_completed::GameCompletedPlugin,
     game_over::GameOverPlugin,
     game_state::{GameState, PauseState},
     mission::MissionPlugin,
     pause_screen::PausePlugin,
+    run_rng::RunRng,
+    save::{ResumeRun, SavePlugin},
     story::StoryPlugin,
-    world_map::WorldMapPlugin,
+    world_map::{map_types::WorldMapState, WorldMapPlugin},
 };
 
 pub use self::encounter::{Challengers, Locations, Players};
@@ -42,6 +57,10 @@ impl Plugin for InGamePlugin {
             MissionPlugin,
             GameOverPlugin,
             GameCompletedPlugin,
+            SavePlugin,
+            ContentValidationPlugin,
+            DifficultyPlugin,
+            EndlessPlugin,
         ))
         .add_state::<GameState>()
         .register_type::<GameState>()
@@ -65,8 +84,36 @@ struct InGame;
 #[derive(ScheduleLabel, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct InGameUpdate;
 
-fn setup(mut commands: Commands, assets: Res<MainGameAssets>) {
-    commands.insert_resource(NextState(Some(GameState::WorldMap)));
+fn setup(
+    mut commands: Commands,
+    assets: Res<MainGameAssets>,
+    resume: Option<Res<ResumeRun>>,
+    mut global_rng: ResMut<GlobalRng>,
+) {
+    let seed = resume
+        .as_ref()
+        .map(|r| r.0.seed)
+        .unwrap_or_else(|| global_rng.get_mut().u64(..));
+    info!("Run seed: {seed}");
+    commands.insert_resource(RunRng::new(seed));
+    commands.insert_resource(
+        resume
+            .as_ref()
+            .map(|r| r.0.heroes.clone())
+            .unwrap_or_default(),
+    );
+    commands.insert_resource(
+        resume
+            .as_ref()
+            .map(|r| r.0.encounter_log.clone())
+            .unwrap_or_default(),
+    );
+    commands.insert_resource(NextState(Some(
+        resume
+            .as_ref()
+            .map(|r| r.0.game_state)
+            .unwrap_or(GameState::WorldMap),
+    )));
     commands.insert_resource(AmbientLight {
         color: Color::rgba_u8(32, 20, 19, 255),
         brightness: 0.02,
@@ -88,17 +135,43 @@ fn setup(mut commands: Commands, assets: Res<MainGameAssets>) {
                 },
             });
 
-            p.spawn(Power::SplitDice);
-            p.spawn(Power::Advantage);
-            p.spawn(Power::StaticBonus(2));
-            p.spawn(Power::AddDice(dice_pools::DiceType::D3));
+            if let Some(resume) = &resume {
+                for (faction, power) in resume.0.powers.iter() {
+                    p.spawn((*power, *faction));
+                }
+            } else {
+                p.spawn((Power::SplitDice, Faction::Knights));
+                p.spawn((Power::StaticBonus(2), Faction::Knights));
+                p.spawn((Power::Advantage, Faction::Druids));
+                p.spawn((Power::AddDice(dice_pools::DiceType::D3), Faction::Druids));
+            }
         });
 }
 
+/// Gives a faction a power for the rest of the run. It's kept under the run's root,
+/// so it goes away with the run rather than carrying over into the next one.
+pub fn grant_power(commands: &mut Commands, power: Power, faction: Faction) {
+    commands.add(move |world: &mut World| {
+        let root = world
+            .query_filtered::<Entity, With<InGame>>()
+            .iter(world)
+            .next();
+        let power = world.spawn((power, faction)).id();
+        if let Some(root) = root {
+            world.entity_mut(root).add_child(power);
+        }
+    });
+}
+
 fn exit(mut commands: Commands, query: Query<Entity, With<InGame>>) {
     commands.insert_resource(NextState(Some(GameState::None)));
     commands.insert_resource(NextState(Some(PauseState::None)));
     commands.insert_resource(NextState(Some(EncounterState::None)));
+    commands.remove_resource::<RunRng>();
+    commands.remove_resource::<Heroes>();
+    commands.remove_resource::<EncounterLog>();
+    commands.remove_resource::<WorldMapState>();
+    commands.remove_resource::<EndlessRun>();
     for item in query.iter() {
         commands.entity(item).despawn_recursive();
     }
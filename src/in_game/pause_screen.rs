@@ -11,7 +11,7 @@ use crate::{
     },
 };
 
-use super::game_state::PauseState;
+use super::{game_state::PauseState, run_rng::RunRng};
 pub struct PausePlugin;
 
 impl Plugin for PausePlugin {
@@ -44,7 +44,7 @@ enum Buttons {
     Menu,
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, run_rng: Option<Res<RunRng>>) {
     let mut resume_button = None;
     let mut menu_button = None;
     let r = root((overlay, c_root), &asset_server, &mut commands, |p| {
@@ -69,6 +69,14 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
                 p,
             )
             .set(&mut menu_button);
+            if let Some(run_rng) = &run_rng {
+                text(
+                    format!("Seed: {}", run_rng.seed()),
+                    (),
+                    (standard_text, button_text),
+                    p,
+                );
+            }
         });
     });
     commands.entity(r).insert(Screen);
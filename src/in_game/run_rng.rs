@@ -0,0 +1,75 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_turborand::RngComponent;
+
+use super::{mission::mission_types::MissionStage, story::PhaseRound};
+
+/// The independent random streams a run draws from. Each is re-seeded from the
+/// run seed at every checkpoint, so a seed reproduces the same map, rewards and
+/// rolls regardless of the order the player does things in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    Map,
+    Missions,
+    Rewards,
+    Story,
+    Challengers,
+    Dice,
+    Encounters,
+    /// Powers that roll, like pre-rolling, so using them doesn't shift the action rolls.
+    Powers,
+}
+
+#[derive(Resource)]
+pub struct RunRng {
+    seed: u64,
+    streams: HashMap<RngStream, (Vec<u64>, RngComponent)>,
+}
+
+impl RunRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::default(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Gets the generator for a stream, re-seeding it if the checkpoint has moved on.
+    pub fn stream(&mut self, stream: RngStream, checkpoint: &[u64]) -> &mut RngComponent {
+        let seed = stream_seed(self.seed, stream, checkpoint);
+        let entry = self
+            .streams
+            .entry(stream)
+            .or_insert_with(|| (checkpoint.to_vec(), RngComponent::with_seed(seed)));
+        if entry.0 != checkpoint {
+            *entry = (checkpoint.to_vec(), RngComponent::with_seed(seed));
+        }
+        &mut entry.1
+    }
+}
+
+/// Identifies the point in the run a random decision is made at.
+pub fn checkpoint(round: &PhaseRound, stage: Option<&MissionStage>) -> [u64; 3] {
+    [
+        round.1 as u64,
+        round.0 as u64,
+        stage.map(|s| s.depth() as u64 + 1).unwrap_or_default(),
+    ]
+}
+
+/// Mixes the seed, stream and checkpoint with splitmix64, which keeps seeds
+/// stable across platforms and compiler versions.
+fn stream_seed(seed: u64, stream: RngStream, checkpoint: &[u64]) -> u64 {
+    let mix = |value: u64| {
+        let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    };
+    checkpoint
+        .iter()
+        .fold(mix(seed ^ mix(stream as u64)), |acc, part| mix(acc ^ part))
+}
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{app_state::AppState, storage};
+
+use super::{
+    difficulty::Difficulty,
+    encounter::{encounter_log::EncounterLog, heroes::Heroes, powers::Power},
+    endless::EndlessRun,
+    factions::Faction,
+    game_state::GameState,
+    mission::{
+        finish_mission,
+        mission_types::{Mission, MissionStage},
+        RewardPicks,
+    },
+    run_rng::RunRng,
+    story::{Phase, PhaseRound, Story, StoryProgress},
+    world_map::{map_types::WorldMapState, PotentialMissions},
+};
+
+const SAVE_KEY: &str = "the-just-two-run";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::WorldMap), request_save)
+            .add_systems(OnEnter(GameState::Mission), request_save)
+            .add_systems(OnEnter(GameState::Failed), clear_save)
+            .add_systems(OnEnter(GameState::Complete), clear_save)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                resume_run.run_if(resource_exists::<ResumeRun>()),
+            )
+            .add_systems(
+                Update,
+                save_run.run_if(
+                    in_state(AppState::InGame).and_then(resource_exists::<SaveRequested>()),
+                ),
+            );
+    }
+}
+
+/// Everything needed to pick a run back up from the world map or a mission.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RunSnapshot {
+    #[serde(default)]
+    pub seed: u64,
+    pub game_state: GameState,
+    pub powers: Vec<(Faction, Power)>,
+    #[serde(default)]
+    pub heroes: Heroes,
+    pub story: Story,
+    pub phase: Phase,
+    pub phase_round: PhaseRound,
+    #[serde(default)]
+    pub progress: StoryProgress,
+    pub mission: Option<(Mission, MissionStage)>,
+    pub potential_missions: Option<PotentialMissions>,
+    #[serde(default)]
+    pub world_map: Option<WorldMapState>,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub endless: Option<EndlessRun>,
+    #[serde(default)]
+    pub encounter_log: EncounterLog,
+    /// Rewards from a completed mission that haven't been picked yet.
+    #[serde(default)]
+    pub reward_picks: Option<RewardPicks>,
+}
+
+/// Inserted before entering [`AppState::InGame`] to continue a saved run rather than start a new one.
+#[derive(Resource, Clone)]
+pub struct ResumeRun(pub RunSnapshot);
+
+/// Saving waits a frame after entering a state, so anything spawned on entry is captured.
+#[derive(Resource)]
+pub struct SaveRequested;
+
+pub fn load_saved_run() -> Option<RunSnapshot> {
+    let data = storage::read(SAVE_KEY)?;
+    match serde_json::from_str(&data) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            error!("Couldn't load saved run: {e}");
+            None
+        }
+    }
+}
+
+fn request_save(mut commands: Commands) {
+    commands.insert_resource(SaveRequested);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_run(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    run_rng: Res<RunRng>,
+    powers: Query<(&Power, &Faction)>,
+    heroes: Option<Res<Heroes>>,
+    story: Option<Res<Story>>,
+    phase: Option<Res<Phase>>,
+    phase_round: Option<Res<PhaseRound>>,
+    progress: Option<Res<StoryProgress>>,
+    // Kept together so the system stays within Bevy's parameter limit.
+    (mission, mission_stage): (Option<Res<Mission>>, Option<Res<MissionStage>>),
+    potential_missions: Option<Res<PotentialMissions>>,
+    map_state: Option<Res<WorldMapState>>,
+    difficulty: Res<Difficulty>,
+    endless: Option<Res<EndlessRun>>,
+    encounter_log: Option<Res<EncounterLog>>,
+    reward_picks: Option<Res<RewardPicks>>,
+) {
+    commands.remove_resource::<SaveRequested>();
+    let (Some(story), Some(phase), Some(phase_round)) = (story, phase, phase_round) else {
+        return;
+    };
+
+    let mut game_state = *state.get();
+    let mut phase_round = phase_round.clone();
+    let mut progress = progress.map(|p| p.clone()).unwrap_or_default();
+    let mut map_state = map_state.map(|m| m.clone());
+    let mut mission = mission
+        .zip(mission_stage)
+        .map(|(m, s)| (m.clone(), s.clone()));
+
+    let reward_picks = reward_picks
+        .filter(|picks| !picks.is_empty())
+        .map(|picks| picks.clone());
+
+    // A completed or abandoned mission is over, so resuming should return
+    // to the world map as though the player had moved on - unless there
+    // are still rewards to pick.
+    if let Some((completed, stage)) = &mission {
+        let finished = completed.open_nodes(stage).is_empty() && reward_picks.is_none();
+        if game_state == GameState::Mission && finished {
+            game_state = GameState::WorldMap;
+            finish_mission(
+                completed,
+                stage,
+                &mut phase_round,
+                &mut progress,
+                map_state.as_mut(),
+            );
+            mission = None;
+        }
+    }
+    let mut heroes = heroes.map(|h| h.clone()).unwrap_or_default();
+    if game_state == GameState::WorldMap {
+        mission = None;
+        heroes.finish_mission();
+    }
+
+    let snapshot = RunSnapshot {
+        seed: run_rng.seed(),
+        game_state,
+        powers: powers
+            .iter()
+            .map(|(power, faction)| (*faction, *power))
+            .collect(),
+        heroes,
+        story: story.clone(),
+        phase: phase.clone(),
+        phase_round,
+        progress,
+        mission,
+        potential_missions: potential_missions
+            .filter(|_| game_state == GameState::WorldMap)
+            .map(|p| p.clone()),
+        world_map: map_state,
+        difficulty: *difficulty,
+        endless: endless.map(|e| e.clone()),
+        encounter_log: encounter_log.map(|l| l.for_save()).unwrap_or_default(),
+        reward_picks: reward_picks.filter(|_| game_state == GameState::Mission),
+    };
+
+    match serde_json::to_string(&snapshot) {
+        Ok(data) => storage::write(SAVE_KEY, &data),
+        Err(e) => error!("Couldn't save run: {e}"),
+    }
+}
+
+fn clear_save() {
+    storage::remove(SAVE_KEY);
+}
+
+fn resume_run(mut commands: Commands, resume: Res<ResumeRun>) {
+    let snapshot = resume.0.clone();
+    commands.insert_resource(snapshot.story);
+    commands.insert_resource(snapshot.phase);
+    commands.insert_resource(snapshot.phase_round);
+    commands.insert_resource(snapshot.progress);
+    commands.insert_resource(snapshot.difficulty);
+    if let Some(endless) = snapshot.endless {
+        commands.insert_resource(endless);
+    }
+    if let Some((mission, stage)) = snapshot.mission {
+        commands.insert_resource(mission);
+        commands.insert_resource(stage);
+    }
+    if let Some(potential_missions) = snapshot.potential_missions {
+        commands.insert_resource(potential_missions);
+    }
+    if let Some(map_state) = snapshot.world_map {
+        commands.insert_resource(map_state);
+    }
+    if let Some(reward_picks) = snapshot.reward_picks {
+        commands.insert_resource(reward_picks);
+    }
+    commands.remove_resource::<ResumeRun>();
+}
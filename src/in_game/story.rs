@@ -1,11 +1,21 @@
 use bevy::{prelude::*, reflect::TypeUuid};
 use bevy_common_assets::yaml::YamlAssetPlugin;
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
-use bevy_turborand::{DelegatedRng, GlobalRng, TurboRand};
-use serde::Deserialize;
+use bevy_turborand::{DelegatedRng, TurboRand};
+use serde::{Deserialize, Serialize};
 
 use crate::{app_state::AppState, assets::MainGameAssets};
 
+use super::{
+    encounter::{heroes::Heroes, powers::Power},
+    endless::EndlessRun,
+    factions::Faction,
+    mission::{mission_types::Missions, rewards::RewardTable},
+    run_rng::{checkpoint, RngStream, RunRng},
+    save::ResumeRun,
+    Players,
+};
+
 use super::game_state::GameState;
 
 pub struct StoryPlugin;
@@ -15,35 +25,178 @@ impl Plugin for StoryPlugin {
         app.register_type::<Story>()
             .register_type::<Phase>()
             .register_type::<PhaseRound>()
+            .register_type::<PhaseTransition>()
+            .register_type::<StoryCondition>()
+            .register_type::<StoryTarget>()
+            .register_type::<Ending>()
+            .register_type::<StoryProgress>()
+            .register_type::<StoryDifficulty>()
             .add_plugins(YamlAssetPlugin::<Story>::new(&["st.yaml"]))
-            .add_systems(OnEnter(AppState::InGame), start_story)
+            .add_systems(
+                OnEnter(AppState::InGame),
+                start_story.run_if(not(resource_exists::<ResumeRun>())),
+            )
             .add_systems(Update, check_phase.run_if(in_state(AppState::InGame)));
     }
 }
 
-#[derive(Resource, Default, Reflect, Deserialize, InspectorOptions, Clone)]
+#[derive(Resource, Default, Reflect, Deserialize, Serialize, InspectorOptions, Clone)]
 #[reflect(Resource, InspectorOptions)]
 pub struct PhaseRound(pub usize, pub usize);
 
-#[derive(Resource, Default, Reflect, Deserialize, InspectorOptions, Clone)]
+/// Something about the run so far that a phase transition can require.
+#[derive(Reflect, Deserialize, Serialize, InspectorOptions, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub enum StoryCondition {
+    MissionCompleted(String),
+    /// Held by one hero, or between the two of them if no faction is given.
+    PowersHeld {
+        count: usize,
+        #[serde(default)]
+        faction: Option<Faction>,
+    },
+    HeroHealth {
+        faction: Faction,
+        #[serde(default)]
+        min: usize,
+        #[serde(default)]
+        max: Option<usize>,
+    },
+}
+
+/// What story conditions are checked against.
+pub struct StoryState<'a> {
+    pub progress: &'a StoryProgress,
+    pub heroes: Option<&'a Heroes>,
+    pub players: Option<&'a Players>,
+    pub powers: Vec<Faction>,
+}
+
+impl StoryCondition {
+    pub fn met(&self, state: &StoryState) -> bool {
+        match self {
+            StoryCondition::MissionCompleted(mission) => {
+                state.progress.completed_missions.contains(mission)
+            }
+            StoryCondition::PowersHeld { count, faction } => {
+                let held = state
+                    .powers
+                    .iter()
+                    .filter(|f| faction.map_or(true, |faction| **f == faction))
+                    .count();
+                held >= *count
+            }
+            StoryCondition::HeroHealth { faction, min, max } => {
+                let hero = state
+                    .players
+                    .and_then(|players| players.get(faction.player_key()));
+                let (Some(heroes), Some(hero)) = (state.heroes, hero) else {
+                    return false;
+                };
+                let health = heroes.get(*faction).current_health(hero);
+                health >= *min && max.map_or(true, |max| health <= max)
+            }
+        }
+    }
+}
+
+#[derive(Reflect, Deserialize, Serialize, InspectorOptions, Clone, Debug)]
+#[reflect(InspectorOptions)]
+pub enum StoryTarget {
+    Phase(String),
+    Ending(String),
+}
+
+impl Default for StoryTarget {
+    fn default() -> Self {
+        Self::Ending(String::new())
+    }
+}
+
+/// Where the story goes from a phase, once all of the conditions are met.
+#[derive(Reflect, Deserialize, Serialize, InspectorOptions, Clone, Debug, Default)]
+#[reflect(InspectorOptions)]
+pub struct PhaseTransition {
+    #[serde(default)]
+    pub conditions: Vec<StoryCondition>,
+    pub to: StoryTarget,
+}
+
+/// How hard a story is, shown when picking one.
+#[derive(Reflect, Deserialize, Serialize, InspectorOptions, Clone, Copy, Debug, Default)]
+#[reflect(InspectorOptions)]
+pub enum StoryDifficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+/// The story picked on the main menu for the next run.
+#[derive(Resource)]
+pub struct SelectedStory(pub Handle<Story>);
+
+/// One of the ways the story can end. Inserted once it's reached, for the completion screen.
+#[derive(Resource, Reflect, Deserialize, Serialize, InspectorOptions, Clone, Debug, Default)]
+#[reflect(Resource, InspectorOptions)]
+pub struct Ending {
+    pub key: String,
+    pub title: String,
+    pub text: String,
+}
+
+/// The missions completed over the whole story, by key.
+#[derive(Resource, Default, Reflect, Deserialize, Serialize, InspectorOptions, Clone)]
+#[reflect(Resource, InspectorOptions)]
+pub struct StoryProgress {
+    pub completed_missions: Vec<String>,
+}
+
+#[derive(Resource, Default, Reflect, Deserialize, Serialize, InspectorOptions, Clone)]
 #[reflect(Resource, InspectorOptions)]
 pub struct Phase {
+    #[serde(default)]
+    pub key: String,
     pub min_missions: usize,
     pub max_missions: usize,
     pub simulatneous_missions: usize,
     pub missions: Vec<String>,
+    /// Rewards for completing the phase's missions, unless a mission has its own.
+    #[serde(default)]
+    pub rewards: Option<RewardTable>,
+    /// Checked in order once `min_missions` are done. A transition with conditions is
+    /// taken as soon as they're met, and one without has an even chance after each
+    /// mission. If none are met by `max_missions`, the last is taken regardless.
+    /// Without any, the story moves on to the following phase. Endless phases are
+    /// generated, and simply lead on to the next one.
+    #[serde(default)]
+    pub next: Vec<PhaseTransition>,
 }
 
-#[derive(Resource, Default, Reflect, InspectorOptions, Deserialize, TypeUuid, Clone)]
+#[derive(Resource, Default, Reflect, InspectorOptions, Deserialize, Serialize, TypeUuid, Clone)]
 #[reflect(Resource, InspectorOptions)]
 #[uuid = "bf4f4ba3-b7bd-4954-b51f-011455c7ff0d"]
 pub struct Story {
     pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub difficulty: StoryDifficulty,
     pub phases: Vec<Phase>,
+    #[serde(default)]
+    pub endings: Vec<Ending>,
 }
 
-fn start_story(mut commands: Commands, assets: Res<MainGameAssets>, stories: Res<Assets<Story>>) {
-    let Some(story) = stories.get(&assets.story) else {
+fn start_story(
+    mut commands: Commands,
+    assets: Res<MainGameAssets>,
+    stories: Res<Assets<Story>>,
+    selected: Option<Res<SelectedStory>>,
+) {
+    let handle = selected
+        .map(|s| s.0.clone())
+        .or_else(|| assets.stories.first().cloned());
+    let Some(story) = handle.and_then(|handle| stories.get(&handle)) else {
         return;
     };
     let story = story.clone();
@@ -55,14 +208,23 @@ fn start_story(mut commands: Commands, assets: Res<MainGameAssets>, stories: Res
     commands.insert_resource(story);
     commands.insert_resource(phase);
     commands.insert_resource(PhaseRound::default());
+    commands.insert_resource(StoryProgress::default());
 }
 
+#[allow(clippy::too_many_arguments)]
 fn check_phase(
     mut commands: Commands,
     round: Res<PhaseRound>,
     phase: Res<Phase>,
     story: Res<Story>,
-    mut global_rng: ResMut<GlobalRng>,
+    progress: Option<Res<StoryProgress>>,
+    heroes: Option<Res<Heroes>>,
+    powers: Query<&Faction, With<Power>>,
+    players: Res<Assets<Players>>,
+    assets: Res<MainGameAssets>,
+    mut run_rng: ResMut<RunRng>,
+    missions: Res<Assets<Missions>>,
+    endless: Option<ResMut<EndlessRun>>,
 ) {
     if !round.is_changed() {
         return;
@@ -70,14 +232,49 @@ fn check_phase(
     if round.0 < phase.min_missions {
         return;
     }
-    let rng = global_rng.get_mut();
-    if round.0 >= phase.max_missions || rng.bool() {
-        let next_phase = round.1 + 1;
-        if let Some(phase) = story.phases.get(next_phase) {
-            commands.insert_resource(phase.clone());
-            commands.insert_resource(PhaseRound(0, next_phase));
-        } else {
-            commands.insert_resource(NextState(Some(GameState::Complete)));
-        }
+    let no_progress = StoryProgress::default();
+    let state = StoryState {
+        progress: progress.as_deref().unwrap_or(&no_progress),
+        heroes: heroes.as_deref(),
+        players: players.get(&assets.players),
+        powers: powers.iter().copied().collect(),
+    };
+    let transition = phase
+        .next
+        .iter()
+        .find(|t| t.conditions.iter().all(|c| c.met(&state)));
+    let conditions_met = transition.is_some_and(|t| !t.conditions.is_empty());
+    let rng = run_rng
+        .stream(RngStream::Story, &checkpoint(&round, None))
+        .get_mut();
+    if !(conditions_met || round.0 >= phase.max_missions || rng.bool()) {
+        return;
+    }
+
+    if let (Some(mut endless), Some(missions)) = (endless, missions.get(&assets.missions)) {
+        endless.start_next_phase(&story, missions, &mut run_rng, &mut commands);
+        return;
+    }
+
+    let to = transition.or(phase.next.last()).map(|t| t.to.clone());
+    let next_phase = match &to {
+        None => Some(round.1 + 1),
+        Some(StoryTarget::Phase(key)) => story.phases.iter().position(|p| &p.key == key),
+        Some(StoryTarget::Ending(_)) => None,
+    };
+    if let Some((index, phase)) = next_phase.and_then(|i| story.phases.get(i).map(|p| (i, p))) {
+        commands.insert_resource(phase.clone());
+        commands.insert_resource(PhaseRound(0, index));
+        return;
+    }
+
+    let ending = match to {
+        Some(StoryTarget::Ending(key)) => story.endings.iter().find(|e| e.key == key).cloned(),
+        _ => None,
+    };
+    match ending {
+        Some(ending) => commands.insert_resource(ending),
+        None => commands.remove_resource::<Ending>(),
     }
+    commands.insert_resource(NextState(Some(GameState::Complete)));
 }
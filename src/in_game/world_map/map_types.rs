@@ -0,0 +1,164 @@
+use bevy::{prelude::*, reflect::TypeUuid, utils::HashMap};
+use bevy_common_assets::yaml::YamlAssetPlugin;
+use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
+use serde::{Deserialize, Serialize};
+
+pub struct WorldMapAssetsPlugin;
+
+impl Plugin for WorldMapAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<MapLocation>()
+            .register_type::<WorldMapLayout>()
+            .register_type::<WorldMapState>()
+            .add_plugins(YamlAssetPlugin::<WorldMapLayout>::new(&["wm.yaml"]));
+    }
+}
+
+/// A `Location.N` node on the world map, keyed by `N` in the layout.
+#[derive(Reflect, InspectorOptions, Clone, Debug, Default, Deserialize)]
+#[reflect(InspectorOptions)]
+pub struct MapLocation {
+    pub name: String,
+    /// Visiting any location in a region reveals the whole region.
+    pub region: String,
+    /// The locations the heroes can travel to from here. Links go both ways.
+    #[serde(default)]
+    pub links: Vec<usize>,
+}
+
+#[derive(Reflect, InspectorOptions, Default, Deserialize, TypeUuid)]
+#[reflect(InspectorOptions)]
+#[uuid = "5d0c3a8e-93f4-4c0e-a7a5-2f3b8e4c61d7"]
+pub struct WorldMapLayout {
+    /// Where the heroes begin every run.
+    pub start: usize,
+    pub locations: HashMap<usize, MapLocation>,
+}
+
+impl WorldMapLayout {
+    pub fn get(&self, index: usize) -> Option<&MapLocation> {
+        self.locations.get(&index)
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.locations
+            .iter()
+            .find(|(_, location)| location.name == name)
+            .map(|(index, _)| *index)
+    }
+
+    /// Every location index, in order.
+    pub fn indices(&self) -> Vec<usize> {
+        let mut indices = self.locations.keys().copied().collect::<Vec<_>>();
+        indices.sort();
+        indices
+    }
+
+    pub fn neighbours(&self, index: usize) -> Vec<usize> {
+        let mut neighbours = self
+            .get(index)
+            .map(|location| location.links.clone())
+            .unwrap_or_default();
+        for (other, location) in self.locations.iter() {
+            if location.links.contains(&index) && !neighbours.contains(other) {
+                neighbours.push(*other);
+            }
+        }
+        neighbours.sort();
+        neighbours
+    }
+
+    /// The shortest route between two locations, not including `from`, or `None`
+    /// if the links never lead there.
+    pub fn path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        let mut previous = vec![(from, from)];
+        let mut index = 0;
+        while let Some((current, _)) = previous.get(index).copied() {
+            if current == to {
+                break;
+            }
+            for next in self.neighbours(current) {
+                if !previous.iter().any(|(visited, _)| *visited == next) {
+                    previous.push((next, current));
+                }
+            }
+            index += 1;
+        }
+        let mut path = vec![];
+        let mut step = to;
+        while step != from {
+            let (_, before) = previous.iter().find(|(visited, _)| *visited == step)?;
+            path.push(step);
+            step = *before;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// Where the heroes are and what they've seen, kept for the whole run.
+#[derive(Resource, Reflect, InspectorOptions, Default, Clone, Serialize, Deserialize)]
+#[reflect(Resource, InspectorOptions)]
+pub struct WorldMapState {
+    pub current: usize,
+    pub visited: Vec<usize>,
+    pub revealed_regions: Vec<String>,
+    /// Locations the heroes have completed a mission at.
+    pub completed: Vec<usize>,
+}
+
+impl WorldMapState {
+    pub fn new(layout: &WorldMapLayout) -> Self {
+        let mut state = Self {
+            current: layout.start,
+            ..default()
+        };
+        state.visit(layout.start, layout);
+        state
+    }
+
+    fn visit(&mut self, index: usize, layout: &WorldMapLayout) {
+        if !self.visited.contains(&index) {
+            self.visited.push(index);
+        }
+        if let Some(location) = layout.get(index) {
+            if !self.revealed_regions.contains(&location.region) {
+                self.revealed_regions.push(location.region.clone());
+            }
+        }
+    }
+
+    pub fn can_reach(&self, index: usize, layout: &WorldMapLayout) -> bool {
+        layout.path(self.current, index).is_some()
+    }
+
+    /// Moves the heroes to a location, visiting everywhere along the way. The heroes
+    /// stay put if there's no route there.
+    pub fn travel(&mut self, to: usize, layout: &WorldMapLayout) -> bool {
+        let Some(path) = layout.path(self.current, to) else {
+            return false;
+        };
+        for step in path {
+            self.visit(step, layout);
+        }
+        self.current = to;
+        true
+    }
+
+    /// Locations in a revealed region, or next to somewhere the heroes have been.
+    pub fn is_revealed(&self, index: usize, layout: &WorldMapLayout) -> bool {
+        layout
+            .get(index)
+            .is_some_and(|location| self.revealed_regions.contains(&location.region))
+            || self
+                .visited
+                .iter()
+                .any(|visited| layout.neighbours(*visited).contains(&index))
+    }
+
+    pub fn complete_current(&mut self) {
+        if !self.completed.contains(&self.current) {
+            self.completed.push(self.current);
+        }
+    }
+}
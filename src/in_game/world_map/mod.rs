@@ -1,9 +1,12 @@
+pub mod map_types;
+
 use bevy::{prelude::*, utils::HashMap};
 
 use bevy_inspector_egui::{prelude::ReflectInspectorOptions, InspectorOptions};
-use bevy_turborand::{DelegatedRng, GlobalRng, TurboRand};
+use bevy_turborand::{DelegatedRng, TurboRand};
 use bevy_ui_dsl::{node, root, text};
 use bevy_vector_shapes::{prelude::ShapePainter, shapes::DiscPainter};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     assets::MainGameAssets,
@@ -20,31 +23,36 @@ use crate::{
 };
 
 use super::{
-    encounter::{encounter_setup_types::Encounters, powers::Power},
+    encounter::{encounter_setup_types::Encounters, heroes::Heroes, powers::Power},
+    factions::Faction,
     game_state::GameState,
     mission::mission_types::{Mission, Missions},
-    story::Phase,
-    InGameUpdate,
+    run_rng::{checkpoint, RngStream, RunRng},
+    story::{Phase, PhaseRound},
+    InGameUpdate, Players,
 };
 
+use self::map_types::{WorldMapAssetsPlugin, WorldMapLayout, WorldMapState};
+
 pub struct WorldMapPlugin;
 
 impl Plugin for WorldMapPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<PotentialMissions>()
+        app.add_plugins(WorldMapAssetsPlugin)
+            .register_type::<PotentialMissions>()
             .register_type::<EncounterLocation>()
             .add_systems(
                 OnEnter(GameState::WorldMap),
                 (
                     spawn_world_map,
-                    draw_available_powers,
-                    generate_potential_missions,
+                    generate_potential_missions.run_if(not(resource_exists::<PotentialMissions>())),
                 ),
             )
             .add_systems(OnExit(GameState::WorldMap), clear_world_map)
             .add_systems(
                 Update,
                 (
+                    draw_available_powers,
                     draw_encounter_locations,
                     find_encounter_locations,
                     draw_encounter_selection_ui,
@@ -54,7 +62,10 @@ impl Plugin for WorldMapPlugin {
             )
             .add_systems(
                 InGameUpdate,
-                (focused_button_activated.pipe(process_input))
+                (
+                    focused_button_activated.pipe(process_input),
+                    focused_button_activated.pipe(transfer_power),
+                )
                     .run_if(in_state(GameState::WorldMap)),
             );
     }
@@ -63,10 +74,10 @@ impl Plugin for WorldMapPlugin {
 #[derive(Component)]
 pub struct WorldMapEntity;
 
-#[allow(dead_code)]
-const NUM_LOCATIONS_ON_MAP: usize = 14;
+/// How many `Location.N` nodes the world map model has.
+pub const NUM_LOCATIONS_ON_MAP: usize = 14;
 
-#[derive(Resource, Reflect, InspectorOptions, Default)]
+#[derive(Resource, Reflect, InspectorOptions, Default, Clone, Serialize, Deserialize)]
 #[reflect(Resource, InspectorOptions)]
 pub struct PotentialMissions(HashMap<usize, Mission>);
 
@@ -116,31 +127,88 @@ fn spawn_world_map(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_potential_missions(
     mut commands: Commands,
     phase: Res<Phase>,
-    mut global_rng: ResMut<GlobalRng>,
+    phase_round: Res<PhaseRound>,
+    mut run_rng: ResMut<RunRng>,
     assets: Res<MainGameAssets>,
     missions: Res<Assets<Missions>>,
     encounters: Res<Assets<Encounters>>,
+    layouts: Res<Assets<WorldMapLayout>>,
+    map_state: Option<Res<WorldMapState>>,
 ) {
-    let (Some(missions), Some(encounters)) = (
+    let (Some(missions), Some(encounters), Some(layout)) = (
         missions.get(&assets.missions),
         encounters.get(&assets.encounters),
+        layouts.get(&assets.world_map_layout),
     ) else {
         return;
     };
-    let rng = global_rng.get_mut();
-    let select_missions = rng.sample_multiple(&phase.missions, phase.simulatneous_missions);
-    let locations = rng.sample_multiple_iter(0..NUM_LOCATIONS_ON_MAP, phase.simulatneous_missions);
-    let result = locations
+    let map_state = match map_state {
+        Some(map_state) => map_state.clone(),
+        None => {
+            let map_state = WorldMapState::new(layout);
+            commands.insert_resource(map_state.clone());
+            map_state
+        }
+    };
+
+    // Missions tied to named locations can only be offered there, while the
+    // rest turn up anywhere the heroes know about. Either way, the heroes have
+    // to be able to get there.
+    let revealed = layout
+        .indices()
+        .into_iter()
+        .filter(|index| {
+            map_state.is_revealed(*index, layout) && map_state.can_reach(*index, layout)
+        })
+        .collect::<Vec<_>>();
+    let candidates = |key: &str| -> Vec<usize> {
+        match missions.0.get(key) {
+            Some(info) if info.locations.is_empty() => revealed.clone(),
+            Some(info) => info
+                .locations
+                .iter()
+                .filter_map(|name| layout.find(name))
+                .filter(|index| map_state.can_reach(*index, layout))
+                .collect(),
+            None => vec![],
+        }
+    };
+    let placeable = phase
+        .missions
         .iter()
-        .zip(select_missions.iter())
+        .filter(|key| !candidates(key).is_empty())
+        .collect::<Vec<_>>();
+
+    let checkpoint = checkpoint(&phase_round, None);
+    let rng = run_rng.stream(RngStream::Map, &checkpoint).get_mut();
+    let mut placed: Vec<(usize, &String)> = vec![];
+    for key in rng.sample_multiple(&placeable, phase.simulatneous_missions) {
+        let free = candidates(key)
+            .into_iter()
+            .filter(|index| !placed.iter().any(|(placed, _)| placed == index))
+            .collect::<Vec<_>>();
+        if let Some(index) = rng.sample(&free) {
+            placed.push((*index, *key));
+        }
+    }
+
+    let result = placed
+        .into_iter()
         .filter_map(|(a, b)| {
+            let rng = run_rng
+                .stream(
+                    RngStream::Missions,
+                    &[checkpoint.as_slice(), &[a as u64]].concat(),
+                )
+                .get_mut();
             missions
                 .0
                 .get(b.as_str())
-                .map(|v| (*a, v.mission(rng, encounters)))
+                .map(|v| (a, v.mission(b, rng, encounters)))
         })
         .collect();
     commands.insert_resource(PotentialMissions(result));
@@ -159,6 +227,7 @@ fn clear_world_map(
     for entity in world_map_entities.iter() {
         commands.entity(entity).despawn_recursive();
     }
+    commands.remove_resource::<PotentialMissions>();
 }
 
 type EncounterLocationTracking<'w, 's, 'a> =
@@ -175,13 +244,30 @@ fn find_encounter_locations(mut commands: Commands, locations: EncounterLocation
     }
 }
 
+/// Whether a location shows up on the map at all.
+fn is_shown(
+    index: usize,
+    map_state: &WorldMapState,
+    layout: &WorldMapLayout,
+    potential_encounters: &PotentialMissions,
+) -> bool {
+    map_state.is_revealed(index, layout) || potential_encounters.0.contains_key(&index)
+}
+
+#[allow(clippy::too_many_arguments)]
 fn draw_encounter_locations(
     mut painter: ShapePainter,
     camera: Query<(&GlobalTransform, &Camera), With<Camera3d>>,
     camera_2d: Query<(&GlobalTransform, &Camera), With<Camera2d>>,
     locations: Query<(&GlobalTransform, &EncounterLocation)>,
     potential_encounters: Res<PotentialMissions>,
+    map_state: Option<Res<WorldMapState>>,
+    layouts: Res<Assets<WorldMapLayout>>,
+    assets: Res<MainGameAssets>,
 ) {
+    let (Some(map_state), Some(layout)) = (map_state, layouts.get(&assets.world_map_layout)) else {
+        return;
+    };
     let Ok((camera_transform, camera)) = camera.get_single() else {
         return;
     };
@@ -190,9 +276,9 @@ fn draw_encounter_locations(
     };
 
     for (transform, location) in locations.iter() {
-        let Some(_) = potential_encounters.0.get(&location.0) else {
+        if !is_shown(location.0, &map_state, layout, &potential_encounters) {
             continue;
-        };
+        }
         let Some(normalized_coordinates) =
             camera.world_to_ndc(camera_transform, transform.translation())
         else {
@@ -203,37 +289,75 @@ fn draw_encounter_locations(
             continue;
         };
         painter.set_translation(position);
-        painter.color = colors::FAIL_COLOR;
+        if map_state.completed.contains(&location.0) {
+            painter.color = colors::SUCCESS_COLOR;
+            painter.circle(16.);
+        }
+        let (outer, inner) = if location.0 == map_state.current {
+            (colors::PRIMARY_COLOR_BLOCKED, colors::PRIMARY_COLOR)
+        } else if !map_state.can_reach(location.0, layout) {
+            (colors::FAIL_COLOR, colors::CARD_COLOR_BLOCKED)
+        } else if potential_encounters.0.contains_key(&location.0) {
+            (colors::FAIL_COLOR, colors::CRITICAL_FAIL_COLOR)
+        } else {
+            (colors::FAIL_COLOR, colors::CARD_COLOR)
+        };
+        painter.color = outer;
         painter.circle(13.);
-        painter.color = colors::CRITICAL_FAIL_COLOR;
+        painter.color = inner;
         painter.circle(10.);
     }
 }
 
+/// Draws a button for every location the heroes know of, redrawing whenever they've been cleared.
+#[allow(clippy::too_many_arguments)]
 fn draw_encounter_selection_ui(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     potential_encounters: Res<PotentialMissions>,
+    map_state: Option<Res<WorldMapState>>,
+    layouts: Res<Assets<WorldMapLayout>>,
+    assets: Res<MainGameAssets>,
     camera: Query<(&GlobalTransform, &Camera), With<Camera3d>>,
-    locations: Query<(Entity, &GlobalTransform, &EncounterLocation), Added<EncounterLocation>>,
+    locations: Query<(Entity, &GlobalTransform, &EncounterLocation)>,
+    drawn: Query<Entity, With<UiButtonLocation>>,
 ) {
-    if !potential_encounters.is_changed() && locations.is_empty() {
+    if !drawn.is_empty() {
         return;
     }
+    let (Some(map_state), Some(layout)) = (map_state, layouts.get(&assets.world_map_layout)) else {
+        return;
+    };
 
     let Ok((camera_transform, camera)) = camera.get_single() else {
         return;
     };
 
     for (entity, transform, location) in locations.iter() {
-        let Some(encounter) = potential_encounters.0.get(&location.0) else {
+        if !is_shown(location.0, &map_state, layout, &potential_encounters) {
             continue;
-        };
+        }
         let Some(viewport_coordinates) =
             camera.world_to_ndc(camera_transform, transform.translation())
         else {
             continue;
         };
+        let name = layout
+            .get(location.0)
+            .map(|l| l.name.clone())
+            .unwrap_or_default();
+        let label = match potential_encounters.0.get(&location.0) {
+            Some(mission) => format!("{} - {name}", mission.title),
+            None => name,
+        };
+        let reachable = map_state.can_reach(location.0, layout);
+        let label = if location.0 == map_state.current {
+            format!("{label} (here)")
+        } else if !reachable {
+            format!("{label} (blocked)")
+        } else {
+            label
+        };
 
         let mut button = None;
 
@@ -250,17 +374,19 @@ fn draw_encounter_selection_ui(
             &asset_server,
             &mut commands,
             |p| {
+                // Somewhere the heroes can't get to is shown, but can't be picked.
+                if !reachable {
+                    node((encounter_listing.nb(), encounter_blocked.nb()), p, |p| {
+                        text(label.as_str(), (), (standard_text, button_text), p);
+                    });
+                    return;
+                }
                 button = Some(focus_button(
                     encounter_listing.nb(),
                     apply_encounter_state,
                     p,
                     |p| {
-                        text(
-                            encounter.title.as_str(),
-                            (),
-                            (standard_text, button_text),
-                            p,
-                        );
+                        text(label.as_str(), (), (standard_text, button_text), p);
                     },
                 ));
             },
@@ -297,11 +423,17 @@ fn update_encounter_selection_ui_position(
     }
 }
 
+/// Travels to the chosen location, starting its mission if there is one.
+#[allow(clippy::too_many_arguments)]
 fn process_input(
     In(focused): In<Option<Entity>>,
     mut commands: Commands,
     interaction_query: TypedFocusedButtonQuery<'_, '_, '_, UiButton>,
     potential_encounters: Res<PotentialMissions>,
+    map_state: Option<ResMut<WorldMapState>>,
+    layouts: Res<Assets<WorldMapLayout>>,
+    assets: Res<MainGameAssets>,
+    drawn: Query<Entity, With<UiButtonLocation>>,
 ) {
     let Some(focused) = focused else {
         return;
@@ -309,32 +441,112 @@ fn process_input(
     let Some((_, btn)) = interaction_query.get(focused).ok() else {
         return;
     };
-    info!("Got Here...");
+    if let (Some(mut map_state), Some(layout)) = (map_state, layouts.get(&assets.world_map_layout))
+    {
+        if !map_state.travel(btn.0, layout) {
+            return;
+        }
+    }
     if let Some(mission) = potential_encounters.0.get(&btn.0) {
         commands.insert_resource(mission.clone());
-        commands.insert_resource(MissionStage(0));
+        commands.insert_resource(MissionStage::default());
         commands.insert_resource(NextState(Some(GameState::Mission)));
+    } else {
+        for entity in drawn.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
     }
 }
 
+/// Moves a power to the other hero's inventory when activated.
+#[derive(Component)]
+struct TransferPower(Entity);
+
+#[derive(Component)]
+struct PowersPanel;
+
+/// Draws each hero's record and powers, redrawing whenever the panel has been cleared.
 fn draw_available_powers(
     mut commands: Commands,
-    powers: Query<&Power>,
+    panel: Query<Entity, With<PowersPanel>>,
+    powers: Query<(Entity, &Power, &Faction)>,
+    heroes: Option<Res<Heroes>>,
+    players: Res<Assets<Players>>,
     assets: Res<MainGameAssets>,
     asset_server: Res<AssetServer>,
 ) {
+    if !panel.is_empty() {
+        return;
+    }
+    let players = players.get(&assets.players);
+    let mut transfer_buttons = vec![];
     let r = root(map_powers_root, &asset_server, &mut commands, |p| {
-        node(map_powers_container, p, |p| {
-            for power in powers.iter() {
-                node(map_power_card, p, |p| {
-                    power.display_bundle(&assets, 50., p);
+        for faction in [Faction::Knights, Faction::Druids] {
+            node(map_heroes_container, p, |p| {
+                let hero = players.and_then(|players| players.get(faction.player_key()));
+                let title = match (hero, &heroes) {
+                    (Some(hero), Some(heroes)) => {
+                        let record = heroes.get(faction);
+                        let xp = match record.next_level(hero) {
+                            Some(next) => format!("{}/{next} XP", record.xp),
+                            None => format!("{} XP", record.xp),
+                        };
+                        format!(
+                            "{} - Level {} - {xp} - Health {}/{}",
+                            hero.name,
+                            record.level + 1,
+                            record.current_health(hero),
+                            record.max_health(hero).0
+                        )
+                    }
+                    _ => format!("The {faction:?}"),
+                };
+                text(title, (), standard_text, p);
+                node(map_powers_container, p, |p| {
+                    for (entity, power, _) in powers.iter().filter(|(_, _, f)| **f == faction) {
+                        let button = focus_button(
+                            power_card_container.nb(),
+                            apply_power_card_state,
+                            p,
+                            |p| {
+                                power.display_bundle(&assets, 50., p);
+                            },
+                        );
+                        transfer_buttons.push((button, entity));
+                    }
+                    node(map_powers_overlay, p, |_p| {});
                 });
-            }
-            node(map_powers_overlay, p, |_p| {});
-        });
+            });
+        }
     });
 
-    commands
-        .entity(r)
-        .insert((WorldMapEntity, Name::new("Available Powers Panel")));
+    commands.entity(r).insert((
+        WorldMapEntity,
+        PowersPanel,
+        Name::new("Available Powers Panel"),
+    ));
+    for (button, power) in transfer_buttons {
+        commands.entity(button).insert(TransferPower(power));
+    }
+}
+
+fn transfer_power(
+    In(focused): In<Option<Entity>>,
+    mut commands: Commands,
+    interaction_query: TypedFocusedButtonQuery<'_, '_, '_, TransferPower>,
+    mut powers: Query<&mut Faction, With<Power>>,
+    panel: Query<Entity, With<PowersPanel>>,
+) {
+    let Some(focused) = focused else {
+        return;
+    };
+    let Ok((_, transfer)) = interaction_query.get(focused) else {
+        return;
+    };
+    if let Ok(mut faction) = powers.get_mut(transfer.0) {
+        *faction = faction.other();
+    }
+    for entity in panel.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 }
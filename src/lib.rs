@@ -0,0 +1,8 @@
+pub mod app_state;
+pub mod assets;
+pub mod in_game;
+pub mod materialized_scene;
+pub mod menus;
+pub mod storage;
+pub mod toon_material;
+pub mod ui;
@@ -1,15 +1,7 @@
-mod app_state;
-mod assets;
-mod in_game;
-mod materialized_scene;
-mod menus;
-mod toon_material;
-mod ui;
+mod content_check;
 
 use std::time::Duration;
 
-use app_state::AppState;
-use assets::{MainGameAssetPlugin, MainGameAssets};
 use bevy::{
     asset::ChangeWatcher,
     core_pipeline::{clear_color::ClearColorConfig, tonemapping::Tonemapping},
@@ -21,19 +13,28 @@ use bevy_inspector_egui::quick::{StateInspectorPlugin, WorldInspectorPlugin};
 use bevy_sequential_actions::SequentialActionsPlugin;
 use bevy_turborand::prelude::RngPlugin;
 use bevy_vector_shapes::Shape2dPlugin;
-use credits::CreditsPlugin;
-use in_game::InGamePlugin;
-use loading_state::LoadingScreenPlugin;
-use materialized_scene::SceneSpawnerPlugin;
-use menu::MainMenuPlugin;
-use menus::{credits, loading_state, menu};
-use toon_material::ToonMaterialPlugin;
-use ui::{colors::DEFAULT_AMBIENT, UiPlugin};
+use the_just_two::{
+    app_state::AppState,
+    assets::{MainGameAssetPlugin, MainGameAssets},
+    in_game::InGamePlugin,
+    materialized_scene::SceneSpawnerPlugin,
+    menus::{
+        credits::CreditsPlugin, loading_state::LoadingScreenPlugin, menu::MainMenuPlugin,
+        story_select::StorySelectPlugin,
+    },
+    toon_material::ToonMaterialPlugin,
+    ui::{self, colors::DEFAULT_AMBIENT, UiPlugin},
+};
 
 fn main() {
     #[cfg(target_arch = "wasm32")]
     console_error_panic_hook::set_once();
 
+    if let Some(assets) = content_check::from_args(std::env::args().skip(1)) {
+        content_check::run(&assets);
+        return;
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins
@@ -61,6 +62,7 @@ fn main() {
             LoadingScreenPlugin,
             MainMenuPlugin,
             CreditsPlugin,
+            StorySelectPlugin,
             InGamePlugin,
             SceneSpawnerPlugin,
             MainGameAssetPlugin,
@@ -4,8 +4,15 @@ use bevy_ui_dsl::*;
 use crate::{
     app_state::AppState,
     assets::MainGameAssets,
+    in_game::{
+        content::ContentErrors,
+        difficulty::{Difficulty, DifficultySetting},
+        save::{load_saved_run, ResumeRun},
+    },
     ui::{
-        buttons::{focus_text_button, focused_button_activated, TypedFocusedButtonQuery},
+        buttons::{
+            focus_button, focus_text_button, focused_button_activated, TypedFocusedButtonQuery,
+        },
         classes::*,
         colors::SCREEN_BACKGROUND_COLOR,
         intermediary_node_bundles::*,
@@ -19,7 +26,11 @@ impl Plugin for MainMenuPlugin {
             .add_systems(OnExit(AppState::MainMenu), exit)
             .add_systems(
                 Update,
-                (focused_button_activated.pipe(process_input)).run_if(in_state(AppState::MainMenu)),
+                (
+                    focused_button_activated.pipe(process_input),
+                    update_difficulty_labels,
+                )
+                    .run_if(in_state(AppState::MainMenu)),
             );
     }
 }
@@ -29,15 +40,41 @@ struct Screen;
 
 #[derive(Component)]
 enum Buttons {
+    Continue,
     Start,
+    Difficulty,
+    Multiplier(DifficultySetting),
     Credits,
 }
 
-fn setup(mut commands: Commands, _assets: Res<MainGameAssets>, asset_server: Res<AssetServer>) {
+/// Text showing the difficulty preset, or one of its multipliers.
+#[derive(Component)]
+struct DifficultyLabel(Option<DifficultySetting>);
+
+const MULTIPLIERS: [DifficultySetting; 3] = [
+    DifficultySetting::Health,
+    DifficultySetting::Damage,
+    DifficultySetting::Thresholds,
+];
+
+/// How many content errors to list on the menu before summarising the rest.
+const MAX_LISTED_ERRORS: usize = 5;
+
+fn setup(
+    mut commands: Commands,
+    _assets: Res<MainGameAssets>,
+    asset_server: Res<AssetServer>,
+    content_errors: Res<ContentErrors>,
+    difficulty: Res<Difficulty>,
+) {
     commands.insert_resource(ClearColor(SCREEN_BACKGROUND_COLOR));
 
+    let mut continue_button = None;
     let mut start_button = None;
     let mut credits_button = None;
+    let mut difficulty_buttons = vec![];
+    let mut difficulty_labels = vec![];
+    let has_saved_run = load_saved_run().is_some();
 
     let r = root(c_root, &asset_server, &mut commands, |p| {
         node(primary_box, p, |p| {
@@ -50,6 +87,16 @@ fn setup(mut commands: Commands, _assets: Res<MainGameAssets>, asset_server: Res
                 );
                 text("Two", (), (main_text, druid_text, druid_text_color), p);
             });
+            if has_saved_run {
+                focus_text_button(
+                    "Continue",
+                    (c_button.nb(), primary_box_item.nb()),
+                    apply_button_state,
+                    button_text,
+                    p,
+                )
+                .set(&mut continue_button);
+            }
             focus_text_button(
                 "Start Game",
                 (c_button.nb(), primary_box_item.nb()),
@@ -58,6 +105,26 @@ fn setup(mut commands: Commands, _assets: Res<MainGameAssets>, asset_server: Res
                 p,
             )
             .set(&mut start_button);
+            let button = focus_button(
+                (c_button.nb(), primary_box_item.nb()),
+                apply_button_state,
+                p,
+                |p| {
+                    difficulty_labels.push((text(difficulty.label(), (), button_text, p), None));
+                },
+            );
+            difficulty_buttons.push((button, Buttons::Difficulty));
+            node((span.nb(), primary_box_item.nb(), centered.nb()), p, |p| {
+                for setting in MULTIPLIERS {
+                    let button = focus_button(c_button.nb(), apply_button_state, p, |p| {
+                        difficulty_labels.push((
+                            text(difficulty.setting_label(setting), (), button_text, p),
+                            Some(setting),
+                        ));
+                    });
+                    difficulty_buttons.push((button, Buttons::Multiplier(setting)));
+                }
+            });
             focus_text_button(
                 "Credits",
                 (c_button.nb(), primary_box_item.nb()),
@@ -66,15 +133,61 @@ fn setup(mut commands: Commands, _assets: Res<MainGameAssets>, asset_server: Res
                 p,
             )
             .set(&mut credits_button);
+            for e in content_errors.0.iter().take(MAX_LISTED_ERRORS) {
+                text(
+                    e.to_string(),
+                    primary_box_item.nb(),
+                    (standard_text, error_text),
+                    p,
+                );
+            }
+            if content_errors.0.len() > MAX_LISTED_ERRORS {
+                text(
+                    format!(
+                        "...and {} more content errors",
+                        content_errors.0.len() - MAX_LISTED_ERRORS
+                    ),
+                    primary_box_item.nb(),
+                    (standard_text, error_text),
+                    p,
+                );
+            }
         });
     });
     commands.entity(r).insert(Screen);
+    if let Some(continue_button) = continue_button {
+        commands.entity(continue_button).insert(Buttons::Continue);
+    }
     commands
         .entity(start_button.unwrap())
         .insert(Buttons::Start);
     commands
         .entity(credits_button.unwrap())
         .insert(Buttons::Credits);
+    for (button, kind) in difficulty_buttons {
+        commands.entity(button).insert(kind);
+    }
+    for (label, setting) in difficulty_labels {
+        commands.entity(label).insert(DifficultyLabel(setting));
+    }
+}
+
+fn update_difficulty_labels(
+    difficulty: Res<Difficulty>,
+    mut labels: Query<(&mut Text, &DifficultyLabel)>,
+) {
+    if !difficulty.is_changed() {
+        return;
+    }
+    for (mut text, label) in labels.iter_mut() {
+        let value = match label.0 {
+            Some(setting) => difficulty.setting_label(setting),
+            None => difficulty.label(),
+        };
+        if let Some(section) = text.sections.first_mut() {
+            section.value = value;
+        }
+    }
 }
 
 fn exit(mut commands: Commands, query: Query<Entity, With<Screen>>) {
@@ -87,6 +200,7 @@ fn process_input(
     In(focused): In<Option<Entity>>,
     mut commands: Commands,
     interaction_query: TypedFocusedButtonQuery<'_, '_, '_, Buttons>,
+    mut difficulty: ResMut<Difficulty>,
 ) {
     let Some(focused) = focused else {
         return;
@@ -95,7 +209,15 @@ fn process_input(
         return;
     };
     match btn {
-        Buttons::Start => commands.insert_resource(NextState(Some(AppState::InGame))),
+        Buttons::Continue => {
+            if let Some(snapshot) = load_saved_run() {
+                commands.insert_resource(ResumeRun(snapshot));
+                commands.insert_resource(NextState(Some(AppState::InGame)));
+            }
+        }
+        Buttons::Start => commands.insert_resource(NextState(Some(AppState::StorySelect))),
+        Buttons::Difficulty => *difficulty = difficulty.next(),
+        Buttons::Multiplier(setting) => *difficulty = difficulty.step(*setting),
         Buttons::Credits => commands.insert_resource(NextState(Some(AppState::Credits))),
     };
 }
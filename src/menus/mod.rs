@@ -1,3 +1,4 @@
 pub mod credits;
 pub mod loading_state;
 pub mod menu;
+pub mod story_select;
@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use bevy_ui_dsl::*;
+
+use crate::{
+    app_state::AppState,
+    assets::MainGameAssets,
+    in_game::{
+        save::ResumeRun,
+        story::{SelectedStory, Story},
+    },
+    ui::{
+        buttons::{
+            focus_button, focus_text_button, focused_button_activated, TypedFocusedButtonQuery,
+        },
+        classes::*,
+        colors::SCREEN_BACKGROUND_COLOR,
+        intermediary_node_bundles::*,
+    },
+};
+
+pub struct StorySelectPlugin;
+
+impl Plugin for StorySelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::StorySelect), setup)
+            .add_systems(OnExit(AppState::StorySelect), exit)
+            .add_systems(
+                Update,
+                (focused_button_activated.pipe(process_input))
+                    .run_if(in_state(AppState::StorySelect)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct Screen;
+
+#[derive(Component)]
+enum Buttons {
+    Story(Handle<Story>),
+    Back,
+}
+
+fn setup(
+    mut commands: Commands,
+    assets: Res<MainGameAssets>,
+    stories: Res<Assets<Story>>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(ClearColor(SCREEN_BACKGROUND_COLOR));
+
+    let mut listed = assets
+        .stories
+        .iter()
+        .filter_map(|handle| stories.get(handle).map(|story| (handle, story)))
+        .collect::<Vec<_>>();
+    listed.sort_by(|(_, a), (_, b)| a.title.cmp(&b.title));
+
+    let mut story_buttons = vec![];
+    let mut back_button = None;
+
+    let r = root(c_root, &asset_server, &mut commands, |p| {
+        node(primary_box, p, |p| {
+            node((span.nb(), primary_box_main.nb()), p, |p| {
+                text("Choose a", (), (main_text, knight_text), p);
+                text("Story", (), (main_text, druid_text), p);
+            });
+            for (handle, story) in listed.iter() {
+                let button = focus_button(story_button.nb(), apply_story_button_state, p, |p| {
+                    text(&story.title, (), button_text, p);
+                    if !story.description.is_empty() {
+                        text(&story.description, (), standard_text, p);
+                    }
+                    text(
+                        format!("Difficulty: {:?}", story.difficulty),
+                        (),
+                        standard_text,
+                        p,
+                    );
+                });
+                story_buttons.push((button, (*handle).clone()));
+            }
+            focus_text_button(
+                "Back",
+                (c_button.nb(), primary_box_item.nb()),
+                apply_button_state,
+                button_text,
+                p,
+            )
+            .set(&mut back_button);
+        });
+    });
+    commands.entity(r).insert(Screen);
+    for (button, handle) in story_buttons {
+        commands.entity(button).insert(Buttons::Story(handle));
+    }
+    if let Some(back_button) = back_button {
+        commands.entity(back_button).insert(Buttons::Back);
+    }
+}
+
+fn exit(mut commands: Commands, query: Query<Entity, With<Screen>>) {
+    for item in query.iter() {
+        commands.entity(item).despawn_recursive();
+    }
+}
+
+fn process_input(
+    In(focused): In<Option<Entity>>,
+    mut commands: Commands,
+    interaction_query: TypedFocusedButtonQuery<'_, '_, '_, Buttons>,
+) {
+    let Some(focused) = focused else {
+        return;
+    };
+    let Some((_entity, btn)) = interaction_query.get(focused).ok() else {
+        return;
+    };
+    match btn {
+        Buttons::Story(handle) => {
+            commands.remove_resource::<ResumeRun>();
+            commands.insert_resource(SelectedStory(handle.clone()));
+            commands.insert_resource(NextState(Some(AppState::InGame)));
+        }
+        Buttons::Back => commands.insert_resource(NextState(Some(AppState::MainMenu))),
+    }
+}
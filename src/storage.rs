@@ -0,0 +1,105 @@
+//! Small key/value persistence - files next to the executable on native
+//! builds, and the browser's local storage on wasm. Exported files are
+//! downloaded by the browser on wasm.
+
+use bevy::prelude::*;
+
+/// Resolves a file name against the executable's directory, falling back to the
+/// working directory if that can't be found.
+#[cfg(not(target_arch = "wasm32"))]
+fn location(file_name: &str) -> std::path::PathBuf {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(file_name)))
+        .unwrap_or_else(|| std::path::PathBuf::from(file_name))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn path(key: &str) -> std::path::PathBuf {
+    location(&format!("{key}.json"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn read(key: &str) -> Option<String> {
+    std::fs::read_to_string(path(key)).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn write(key: &str, value: &str) {
+    if let Err(e) = std::fs::write(path(key), value) {
+        error!("Couldn't write {key}: {e}");
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn remove(key: &str) {
+    let _ = std::fs::remove_file(path(key));
+}
+
+/// Writes a file for the player to keep, rather than anything the game reads back.
+/// Returns whether it was written.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export(file_name: &str, value: &str) -> bool {
+    match std::fs::write(location(file_name), value) {
+        Ok(()) => true,
+        Err(e) => {
+            error!("Couldn't export {file_name}: {e}");
+            false
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn read(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn write(key: &str, value: &str) {
+    let Some(storage) = local_storage() else {
+        error!("Couldn't access local storage to write {key}");
+        return;
+    };
+    if storage.set_item(key, value).is_err() {
+        error!("Couldn't write {key}");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn remove(key: &str) {
+    if let Some(storage) = local_storage() {
+        let _ = storage.remove_item(key);
+    }
+}
+
+/// Hands the file to the browser as a download, through a temporary link to a blob.
+#[cfg(target_arch = "wasm32")]
+pub fn export(file_name: &str, value: &str) -> bool {
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let download = || -> Option<()> {
+        let document = web_sys::window()?.document()?;
+        let parts = js_sys::Array::of1(&JsValue::from_str(value));
+        let blob = web_sys::Blob::new_with_str_sequence(&parts).ok()?;
+        let url = web_sys::Url::create_object_url_with_blob(&blob).ok()?;
+        let link = document
+            .create_element("a")
+            .ok()?
+            .dyn_into::<web_sys::HtmlAnchorElement>()
+            .ok()?;
+        link.set_href(&url);
+        link.set_download(file_name);
+        link.click();
+        web_sys::Url::revoke_object_url(&url).ok()
+    };
+    let downloaded = download().is_some();
+    if !downloaded {
+        error!("Couldn't export {file_name}");
+    }
+    downloaded
+}
@@ -126,3 +126,28 @@ pub fn card_critical(assets: &AssetServer, t: &mut TextStyle) {
     t.color = CRITICAL_COLOR;
     t.font = assets.load("fonts/AMERSN__.ttf");
 }
+
+pub fn rolled_dice(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().display = Display::Flex;
+    b.style().flex_direction = FlexDirection::Row;
+    b.style().justify_content = JustifyContent::Center;
+    b.style().align_items = AlignItems::Center;
+    b.style().column_gap = Val::Px(10.);
+}
+
+pub fn rolled_die(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().position_type = PositionType::Relative;
+    b.style().width = Val::Px(60.);
+    b.style().height = Val::Px(60.);
+}
+
+pub fn rolled_die_value(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().position_type = PositionType::Absolute;
+    b.style().top = Val::Px(0.);
+    b.style().bottom = Val::Px(0.);
+    b.style().left = Val::Px(0.);
+    b.style().right = Val::Px(0.);
+    b.style().justify_content = JustifyContent::Center;
+    b.style().align_items = AlignItems::Center;
+    b.z_index(ZIndex::Global(5));
+}
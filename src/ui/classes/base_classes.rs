@@ -95,6 +95,23 @@ pub fn apply_button_state(state: FocusState) -> NodeBundle {
     bundle
 }
 
+fn stacked(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().flex_direction = FlexDirection::Column;
+    b.style().row_gap = Val::Px(5.);
+}
+
+pub fn story_button(b: &mut dyn IntermediaryNodeBundleHandler) {
+    c_button(b);
+    primary_box_item(b);
+    stacked(b);
+}
+
+pub fn apply_story_button_state(state: FocusState) -> NodeBundle {
+    let mut bundle = apply_button_state(state);
+    stacked(&mut bundle);
+    bundle
+}
+
 pub fn button_text(assets: &AssetServer, t: &mut TextStyle) {
     t.font_size = 20.;
     t.color = colors::BORDER_COLOR;
@@ -112,6 +129,11 @@ pub fn standard_text(assets: &AssetServer, t: &mut TextStyle) {
     t.font = assets.load("fonts/AMERSN__.ttf");
 }
 
+pub fn error_text(_: &AssetServer, t: &mut TextStyle) {
+    t.font_size = 16.;
+    t.color = colors::CRITICAL_FAIL_COLOR;
+}
+
 pub fn knight_text(assets: &AssetServer, t: &mut TextStyle) {
     t.font = assets.load("fonts/ENDOR___.ttf");
 }
@@ -147,3 +169,15 @@ pub fn dice_pool_modifier(b: &mut dyn IntermediaryNodeBundleHandler) {
     b.style().height = Val::Percent(50.);
     b.z_index(ZIndex::Global(5));
 }
+
+pub fn pre_rolled_value(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().position_type = PositionType::Absolute;
+    b.style().top = Val::Px(0.);
+    b.style().bottom = Val::Px(0.);
+    b.style().left = Val::Px(0.);
+    b.style().right = Val::Px(0.);
+    b.style().justify_content = JustifyContent::Center;
+    b.style().align_items = AlignItems::Center;
+    b.background_color().0 = colors::OVERLAY_COLOR;
+    b.z_index(ZIndex::Global(5));
+}
@@ -107,3 +107,48 @@ pub fn apply_encounter_knight_state(state: FocusState) -> NodeBundle {
     };
     bundle
 }
+
+pub fn turn_order_root(b: &mut NodeBundle) {
+    b.style.position_type = PositionType::Absolute;
+    b.style.top = Val::Px(10.);
+    b.style.left = Val::Px(0.);
+    b.style.width = Val::Percent(100.);
+    b.style.justify_content = JustifyContent::Center;
+    b.style.column_gap = Val::Px(5.);
+}
+
+pub fn turn_order_entry(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().padding = UiRect::all(Val::Px(5.));
+    b.background_color().0 = colors::CARD_COLOR;
+}
+
+pub fn c_encounter_log(b: &mut NodeBundle) {
+    b.style.position_type = PositionType::Absolute;
+    b.style.left = Val::Px(10.);
+    b.style.top = Val::Px(10.);
+    b.style.width = Val::Percent(30.);
+    b.style.height = Val::Percent(40.);
+    b.style.padding = UiRect::all(Val::Px(10.));
+    b.style.overflow = Overflow::clip();
+    b.background_color.0 = colors::OVERLAY_COLOR;
+}
+
+pub fn encounter_log_list(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().position_type = PositionType::Relative;
+    b.style().display = Display::Flex;
+    b.style().flex_direction = FlexDirection::Column;
+    b.style().row_gap = Val::Px(5.);
+    b.style().width = Val::Percent(100.);
+    b.style().align_self = AlignSelf::FlexStart;
+}
+
+pub fn encounter_log_entry(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().display = Display::Flex;
+    b.style().flex_direction = FlexDirection::Column;
+}
+
+pub fn log_detail_text(assets: &AssetServer, t: &mut TextStyle) {
+    t.font_size = 14.;
+    t.color = colors::PRIMARY_COLOR;
+    t.font = assets.load("fonts/AMERSN__.ttf");
+}
@@ -18,6 +18,13 @@ pub fn map_powers_container(b: &mut NodeBundle) {
     b.style.align_items = AlignItems::Center;
 }
 
+pub fn map_heroes_container(b: &mut NodeBundle) {
+    b.style.padding = UiRect::all(Val::Px(5.));
+    b.style.row_gap = Val::Px(5.);
+    b.style.flex_direction = FlexDirection::Column;
+    b.background_color.0 = colors::VISUALIZER_BACKGROUND;
+}
+
 pub fn map_power_card(b: &mut NodeBundle) {
     b.style.flex_grow = 0.;
     b.style.flex_shrink = 0.;
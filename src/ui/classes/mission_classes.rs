@@ -25,3 +25,54 @@ pub fn mission_container(b: &mut NodeBundle) {
 pub fn mission_encounter_title(b: &mut dyn IntermediaryNodeBundleHandler) {
     b.style().padding = UiRect::all(Val::Px(5.));
 }
+
+pub fn mission_rewards_container(b: &mut NodeBundle) {
+    b.style.column_gap = Val::Px(5.);
+    b.style.justify_content = JustifyContent::Center;
+    b.style.align_items = AlignItems::Center;
+}
+
+pub fn reward_label(b: &mut dyn IntermediaryNodeBundleHandler) {
+    b.style().padding = UiRect::all(Val::Px(5.));
+    b.style().align_items = AlignItems::Center;
+}
+
+pub fn mission_graph(b: &mut NodeBundle) {
+    b.style.column_gap = Val::Px(30.);
+    b.style.align_items = AlignItems::Center;
+}
+
+pub fn mission_graph_column(b: &mut NodeBundle) {
+    b.style.row_gap = Val::Px(10.);
+    b.style.flex_direction = FlexDirection::Column;
+    b.style.justify_content = JustifyContent::Center;
+    b.style.align_items = AlignItems::Stretch;
+}
+
+fn mission_node(b: &mut NodeBundle) {
+    b.style.padding = UiRect::all(Val::Px(15.));
+    b.style.flex_direction = FlexDirection::Column;
+    b.style.row_gap = Val::Px(5.);
+    b.style.border = UiRect::all(Val::Px(3.));
+}
+
+pub fn mission_node_current(b: &mut NodeBundle) {
+    mission_node(b);
+    b.background_color.0 = colors::VISUALIZER_BACKGROUND;
+    b.border_color.0 = colors::SUCCESS_COLOR;
+}
+
+pub fn mission_node_visited(b: &mut NodeBundle) {
+    mission_node(b);
+    b.background_color.0 = colors::CARD_COLOR;
+}
+
+pub fn mission_node_reachable(b: &mut NodeBundle) {
+    mission_node(b);
+    b.background_color.0 = colors::VISUALIZER_BACKGROUND;
+}
+
+pub fn mission_node_closed(b: &mut NodeBundle) {
+    mission_node(b);
+    b.background_color.0 = colors::CARD_COLOR_BLOCKED;
+}